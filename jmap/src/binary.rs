@@ -0,0 +1,970 @@
+//! Compact binary container (`.jmapb`) with random access to objects by path.
+//!
+//! Layout (little endian):
+//! - magic `JMAPBIN\0`, u32 container version
+//! - section table: `(u64 offset, u64 length)` for each of the sections below in order
+//! - Header: MessagePack `[format_version, metadata, image_base_address, layout_unknown]`
+//! - Strings: u32 count followed by `(u32 length, utf8)`, each object path referenced once
+//! - Index: u32 count followed by `(u32 path string id, u64 offset, u32 length)` sorted by path
+//! - Objects: concatenated MessagePack encoded objects, offsets relative to the section
+//! - VTables, Names, Diagnostics: MessagePack, only decoded when requested
+//!
+//! Objects are MessagePack arrays so field names are not repeated for every object. References
+//! to other objects (outer, class, children, super struct, CDO and the types of properties)
+//! are ids into the string table. Property values keep the shape they have in JSON.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::ops::Range;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    Address, Class, Diagnostic, EClassCastFlags, EClassFlags, ECppForm, EEnumFlags, EFunctionFlags,
+    EObjectFlags, EPropertyFlags, EStructFlags, Enum, FORMAT_VERSION, Function, Jmap, Metadata,
+    Object, ObjectType, Package, Property, PropertyType, ScriptStruct, Struct, ValuesWrapper,
+};
+
+const MAGIC: &[u8; 8] = b"JMAPBIN\0";
+/// Version of the container layout, the jmap [`FORMAT_VERSION`] is recorded in the header
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy)]
+enum Section {
+    Header,
+    Strings,
+    Index,
+    Objects,
+    VTables,
+    Names,
+    Diagnostics,
+}
+const SECTION_COUNT: usize = 7;
+const SECTION_TABLE_OFFSET: usize = MAGIC.len() + 4;
+const DATA_OFFSET: usize = SECTION_TABLE_OFFSET + SECTION_COUNT * 16;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format_version: u32,
+    metadata: Option<Metadata>,
+    image_base_address: Address,
+    layout_unknown: bool,
+}
+
+/// Index into the string table
+type Id = u32;
+
+#[derive(Serialize, Deserialize)]
+struct BinObject {
+    address: Address,
+    vtable: Address,
+    object_flags: EObjectFlags,
+    outer: Option<Id>,
+    class: Id,
+    children: Vec<Id>,
+    /// Encoded with [`encode_map`]
+    #[serde(with = "bytes")]
+    property_values: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinStruct {
+    object: BinObject,
+    super_struct: Option<Id>,
+    properties: Vec<BinProperty>,
+    properties_size: usize,
+    min_alignment: usize,
+    #[serde(with = "bytes")]
+    script: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinObjectType {
+    Object(BinObject),
+    Package(BinObject),
+    Enum {
+        object: BinObject,
+        cpp_type: String,
+        enum_flags: Option<EEnumFlags>,
+        cpp_form: ECppForm,
+        names: Vec<(String, i64)>,
+    },
+    ScriptStruct {
+        r#struct: BinStruct,
+        struct_flags: EStructFlags,
+    },
+    Class {
+        r#struct: BinStruct,
+        class_flags: EClassFlags,
+        class_cast_flags: EClassCastFlags,
+        class_default_object: Option<Id>,
+        instance_vtable: Option<Address>,
+    },
+    Function {
+        r#struct: BinStruct,
+        function_flags: EFunctionFlags,
+        func: Address,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinProperty {
+    address: Address,
+    name: String,
+    offset: usize,
+    array_dim: usize,
+    size: usize,
+    r#type: BinPropertyType,
+    flags: EPropertyFlags,
+}
+
+/// [`PropertyType`] with references to other objects as string ids
+#[derive(Serialize, Deserialize)]
+enum BinPropertyType {
+    Struct(Id),
+    Str,
+    Name,
+    Text,
+    MulticastInlineDelegate(Option<Id>),
+    MulticastSparseDelegate(Option<Id>),
+    MulticastDelegate(Option<Id>),
+    Delegate(Option<Id>),
+    Bool {
+        field_size: u8,
+        byte_offset: u8,
+        byte_mask: u8,
+        field_mask: u8,
+    },
+    Array(Box<BinProperty>),
+    Enum {
+        container: Box<BinProperty>,
+        r#enum: Option<Id>,
+    },
+    Map {
+        key_prop: Box<BinProperty>,
+        value_prop: Box<BinProperty>,
+    },
+    Set(Box<BinProperty>),
+    Float,
+    Double,
+    Byte(Option<Id>),
+    UInt16,
+    UInt32,
+    UInt64,
+    Int8,
+    Int16,
+    Int,
+    Int64,
+    Object(Id),
+    Class {
+        property_class: Id,
+        meta_class: Id,
+    },
+    WeakObject(Id),
+    SoftObject(Id),
+    SoftClass {
+        property_class: Id,
+        meta_class: Id,
+    },
+    LazyObject(Id),
+    Interface(Id),
+    FieldPath(Option<Id>),
+    Optional(Box<BinProperty>),
+    Utf8Str,
+    AnsiStr,
+}
+
+/// MessagePack bin rather than an array of integers
+mod bytes {
+    use serde::de::{Error, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        struct BytesVisitor;
+        impl Visitor<'_> for BytesVisitor {
+            type Value = Vec<u8>;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+            fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+            fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+        }
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+/// Whether `data` starts with the `.jmapb` magic
+pub fn is_binary(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Structs as arrays. Human readable so bitflags and addresses encode the same way as in JSON.
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut buf = vec![];
+    value.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_human_readable())?;
+    Ok(buf)
+}
+/// Structs as maps, for property values which are read back untyped and must keep their JSON
+/// shape and for types with skipped fields
+fn encode_map<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut buf = vec![];
+    value.serialize(
+        &mut rmp_serde::Serializer::new(&mut buf)
+            .with_struct_map()
+            .with_human_readable(),
+    )?;
+    Ok(buf)
+}
+fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    Ok(T::deserialize(
+        &mut rmp_serde::Deserializer::from_read_ref(data).with_human_readable(),
+    )?)
+}
+
+/// Assigns string ids while encoding
+#[derive(Default)]
+struct StringTable<'a> {
+    ids: HashMap<&'a str, Id>,
+    strings: Vec<&'a str>,
+}
+impl<'a> StringTable<'a> {
+    fn id(&mut self, string: &'a str) -> Id {
+        *self.ids.entry(string).or_insert_with(|| {
+            self.strings.push(string);
+            (self.strings.len() - 1) as Id
+        })
+    }
+    fn opt(&mut self, string: &'a Option<String>) -> Option<Id> {
+        string.as_deref().map(|s| self.id(s))
+    }
+
+    fn object_type(&mut self, obj: &'a ObjectType) -> Result<BinObjectType> {
+        Ok(match obj {
+            ObjectType::Object(obj) => BinObjectType::Object(self.object(obj)?),
+            ObjectType::Package(obj) => BinObjectType::Package(self.object(&obj.object)?),
+            ObjectType::Enum(obj) => BinObjectType::Enum {
+                object: self.object(&obj.object)?,
+                cpp_type: obj.cpp_type.clone(),
+                enum_flags: obj.enum_flags,
+                cpp_form: obj.cpp_form.clone(),
+                names: obj.names.clone(),
+            },
+            ObjectType::ScriptStruct(obj) => BinObjectType::ScriptStruct {
+                r#struct: self.r#struct(&obj.r#struct)?,
+                struct_flags: obj.struct_flags,
+            },
+            ObjectType::Class(obj) => BinObjectType::Class {
+                r#struct: self.r#struct(&obj.r#struct)?,
+                class_flags: obj.class_flags,
+                class_cast_flags: obj.class_cast_flags,
+                class_default_object: self.opt(&obj.class_default_object),
+                instance_vtable: obj.instance_vtable,
+            },
+            ObjectType::Function(obj) => BinObjectType::Function {
+                r#struct: self.r#struct(&obj.r#struct)?,
+                function_flags: obj.function_flags,
+                func: obj.func,
+            },
+        })
+    }
+    fn object(&mut self, obj: &'a Object) -> Result<BinObject> {
+        Ok(BinObject {
+            address: obj.address,
+            vtable: obj.vtable,
+            object_flags: obj.object_flags,
+            outer: self.opt(&obj.outer),
+            class: self.id(&obj.class),
+            children: obj.children.iter().map(|c| self.id(c)).collect(),
+            property_values: encode_map(&obj.property_values)?,
+        })
+    }
+    fn r#struct(&mut self, s: &'a Struct) -> Result<BinStruct> {
+        Ok(BinStruct {
+            object: self.object(&s.object)?,
+            super_struct: self.opt(&s.super_struct),
+            properties: s.properties.iter().map(|p| self.property(p)).collect(),
+            properties_size: s.properties_size,
+            min_alignment: s.min_alignment,
+            script: s.script.clone(),
+        })
+    }
+    fn property(&mut self, prop: &'a Property) -> BinProperty {
+        use BinPropertyType as B;
+        use PropertyType as P;
+
+        let mut boxed = |prop: &'a Property| Box::new(self.property(prop));
+        let r#type = match &prop.r#type {
+            P::Struct { r#struct } => B::Struct(self.id(r#struct)),
+            P::Str => B::Str,
+            P::Name => B::Name,
+            P::Text => B::Text,
+            P::MulticastInlineDelegate { signature_function } => {
+                B::MulticastInlineDelegate(self.opt(signature_function))
+            }
+            P::MulticastSparseDelegate { signature_function } => {
+                B::MulticastSparseDelegate(self.opt(signature_function))
+            }
+            P::MulticastDelegate { signature_function } => {
+                B::MulticastDelegate(self.opt(signature_function))
+            }
+            P::Delegate { signature_function } => B::Delegate(self.opt(signature_function)),
+            P::Bool {
+                field_size,
+                byte_offset,
+                byte_mask,
+                field_mask,
+            } => B::Bool {
+                field_size: *field_size,
+                byte_offset: *byte_offset,
+                byte_mask: *byte_mask,
+                field_mask: *field_mask,
+            },
+            P::Array { inner } => B::Array(boxed(inner)),
+            P::Enum { container, r#enum } => B::Enum {
+                container: boxed(container),
+                r#enum: self.opt(r#enum),
+            },
+            P::Map {
+                key_prop,
+                value_prop,
+            } => B::Map {
+                key_prop: boxed(key_prop),
+                value_prop: boxed(value_prop),
+            },
+            P::Set { key_prop } => B::Set(boxed(key_prop)),
+            P::Float => B::Float,
+            P::Double => B::Double,
+            P::Byte { r#enum } => B::Byte(self.opt(r#enum)),
+            P::UInt16 => B::UInt16,
+            P::UInt32 => B::UInt32,
+            P::UInt64 => B::UInt64,
+            P::Int8 => B::Int8,
+            P::Int16 => B::Int16,
+            P::Int => B::Int,
+            P::Int64 => B::Int64,
+            P::Object { property_class } => B::Object(self.id(property_class)),
+            P::Class {
+                property_class,
+                meta_class,
+            } => B::Class {
+                property_class: self.id(property_class),
+                meta_class: self.id(meta_class),
+            },
+            P::WeakObject { property_class } => B::WeakObject(self.id(property_class)),
+            P::SoftObject { property_class } => B::SoftObject(self.id(property_class)),
+            P::SoftClass {
+                property_class,
+                meta_class,
+            } => B::SoftClass {
+                property_class: self.id(property_class),
+                meta_class: self.id(meta_class),
+            },
+            P::LazyObject { property_class } => B::LazyObject(self.id(property_class)),
+            P::Interface { interface_class } => B::Interface(self.id(interface_class)),
+            P::FieldPath { property_class } => B::FieldPath(self.opt(property_class)),
+            P::Optional { inner } => B::Optional(boxed(inner)),
+            P::Utf8Str => B::Utf8Str,
+            P::AnsiStr => B::AnsiStr,
+        };
+        BinProperty {
+            address: prop.address,
+            name: prop.name.clone(),
+            offset: prop.offset,
+            array_dim: prop.array_dim,
+            size: prop.size,
+            r#type,
+            flags: prop.flags,
+        }
+    }
+}
+
+pub fn write_binary(jmap: &Jmap, mut w: impl Write) -> Result<()> {
+    let mut strings = StringTable::default();
+    let mut index = vec![];
+    let mut objects = vec![];
+
+    index.extend((jmap.objects.len() as u32).to_le_bytes());
+    for (path, obj) in &jmap.objects {
+        let encoded = strings
+            .object_type(obj)
+            .and_then(|obj| encode(&obj))
+            .with_context(|| format!("failed to encode {path}"))?;
+        index.extend(strings.id(path).to_le_bytes());
+        index.extend((objects.len() as u64).to_le_bytes());
+        index.extend((encoded.len() as u32).to_le_bytes());
+        objects.extend(encoded);
+    }
+
+    let mut string_table = vec![];
+    string_table.extend((strings.strings.len() as u32).to_le_bytes());
+    for string in &strings.strings {
+        string_table.extend((string.len() as u32).to_le_bytes());
+        string_table.extend(string.as_bytes());
+    }
+
+    let sections: [Vec<u8>; SECTION_COUNT] = [
+        encode(&Header {
+            format_version: jmap.format_version,
+            metadata: jmap.metadata.clone(),
+            image_base_address: jmap.image_base_address,
+            layout_unknown: jmap.layout_unknown,
+        })?,
+        string_table,
+        index,
+        objects,
+        encode(&jmap.vtables)?,
+        encode(&jmap.names)?,
+        encode_map(&jmap.diagnostics)?,
+    ];
+
+    w.write_all(MAGIC)?;
+    w.write_all(&VERSION.to_le_bytes())?;
+    let mut offset = DATA_OFFSET as u64;
+    for section in &sections {
+        w.write_all(&offset.to_le_bytes())?;
+        w.write_all(&(section.len() as u64).to_le_bytes())?;
+        offset += section.len() as u64;
+    }
+    for section in &sections {
+        w.write_all(section)?;
+    }
+    Ok(())
+}
+
+/// Lazily decoded `.jmapb`. Only the string table and path index are read up front, objects
+/// and remaining sections are decoded on access.
+pub struct JmapBinary<D> {
+    data: D,
+    sections: [Range<usize>; SECTION_COUNT],
+    header: Header,
+    /// Ranges of the strings in the string table
+    strings: Vec<Range<usize>>,
+    /// Sorted by path: (path, object range within the objects section)
+    index: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl<D: AsRef<[u8]>> JmapBinary<D> {
+    pub fn new(data: D) -> Result<Self> {
+        let bytes = data.as_ref();
+        if !is_binary(bytes) {
+            bail!("not a binary jmap");
+        }
+        let version = read_u32(bytes, MAGIC.len())?;
+        if version != VERSION {
+            bail!("unsupported binary jmap version {version}");
+        }
+
+        let mut sections: [Range<usize>; SECTION_COUNT] = Default::default();
+        for (i, section) in sections.iter_mut().enumerate() {
+            let entry = SECTION_TABLE_OFFSET + i * 16;
+            let start = read_u64(bytes, entry)? as usize;
+            let len = read_u64(bytes, entry + 8)? as usize;
+            if start + len > bytes.len() {
+                bail!("section {i} out of bounds, file truncated?");
+            }
+            *section = start..start + len;
+        }
+
+        let header: Header = decode(&bytes[sections[Section::Header as usize].clone()])?;
+        if header.format_version != FORMAT_VERSION {
+            bail!(
+                "binary jmap has format version {}, only version {FORMAT_VERSION} can be read, \
+                 convert it from JSON again",
+                header.format_version
+            );
+        }
+
+        let string_table = &bytes[sections[Section::Strings as usize].clone()];
+        let strings_base = sections[Section::Strings as usize].start;
+        let mut strings = vec![];
+        let mut pos = 4;
+        for _ in 0..read_u32(string_table, 0)? {
+            let len = read_u32(string_table, pos)? as usize;
+            let start = strings_base + pos + 4;
+            std::str::from_utf8(
+                bytes
+                    .get(start..start + len)
+                    .context("string out of bounds")?,
+            )?;
+            strings.push(start..start + len);
+            pos += 4 + len;
+        }
+
+        let index_data = &bytes[sections[Section::Index as usize].clone()];
+        let mut index = vec![];
+        for i in 0..read_u32(index_data, 0)? as usize {
+            let entry = 4 + i * 16;
+            let path = read_u32(index_data, entry)? as usize;
+            let offset = read_u64(index_data, entry + 4)? as usize;
+            let len = read_u32(index_data, entry + 12)? as usize;
+            let path = strings.get(path).context("path id out of bounds")?.clone();
+            index.push((path, offset..offset + len));
+        }
+
+        Ok(Self {
+            data,
+            sections,
+            header,
+            strings,
+            index,
+        })
+    }
+
+    fn section(&self, section: Section) -> &[u8] {
+        &self.data.as_ref()[self.sections[section as usize].clone()]
+    }
+    fn str(&self, range: &Range<usize>) -> &str {
+        // validated in new
+        std::str::from_utf8(&self.data.as_ref()[range.clone()]).unwrap()
+    }
+    fn string(&self, id: Id) -> Result<String> {
+        let range = self
+            .strings
+            .get(id as usize)
+            .with_context(|| format!("string id {id} out of bounds"))?;
+        Ok(self.str(range).to_string())
+    }
+    fn opt(&self, id: Option<Id>) -> Result<Option<String>> {
+        id.map(|id| self.string(id)).transpose()
+    }
+
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.header.metadata.as_ref()
+    }
+    pub fn image_base_address(&self) -> Address {
+        self.header.image_base_address
+    }
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+    /// All object paths in sorted order
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.index.iter().map(|(path, _)| self.str(path))
+    }
+    pub fn contains(&self, path: &str) -> bool {
+        self.find(path).is_some()
+    }
+    fn find(&self, path: &str) -> Option<&Range<usize>> {
+        self.index
+            .binary_search_by(|(p, _)| self.str(p).cmp(path))
+            .ok()
+            .map(|i| &self.index[i].1)
+    }
+    /// Decode a single object
+    pub fn get(&self, path: &str) -> Result<Option<ObjectType>> {
+        let Some(range) = self.find(path) else {
+            return Ok(None);
+        };
+        Ok(Some(self.decode_object(path, range)?))
+    }
+    fn decode_object(&self, path: &str, range: &Range<usize>) -> Result<ObjectType> {
+        let data = self
+            .section(Section::Objects)
+            .get(range.clone())
+            .with_context(|| format!("object {path} out of bounds"))?;
+        decode(data)
+            .and_then(|obj| self.object_type(obj))
+            .with_context(|| format!("failed to decode {path}"))
+    }
+    pub fn vtables(&self) -> Result<BTreeMap<Address, Vec<Address>>> {
+        decode(self.section(Section::VTables))
+    }
+    pub fn names(&self) -> Result<Option<BTreeMap<u32, String>>> {
+        decode(self.section(Section::Names))
+    }
+    pub fn diagnostics(&self) -> Result<Vec<Diagnostic>> {
+        decode(self.section(Section::Diagnostics))
+    }
+
+    /// Decode everything
+    pub fn to_jmap(&self) -> Result<Jmap> {
+        let mut objects = BTreeMap::new();
+        for (path, range) in &self.index {
+            let path = self.str(path);
+            objects.insert(path.to_string(), self.decode_object(path, range)?);
+        }
+        Ok(Jmap {
+            format_version: self.header.format_version,
+            metadata: self.header.metadata.clone(),
+            image_base_address: self.header.image_base_address,
+            layout_unknown: self.header.layout_unknown,
+            objects,
+            vtables: self.vtables()?,
+            names: self.names()?,
+            diagnostics: self.diagnostics()?,
+        })
+    }
+
+    fn object_type(&self, obj: BinObjectType) -> Result<ObjectType> {
+        Ok(match obj {
+            BinObjectType::Object(obj) => ObjectType::Object(self.object(obj)?),
+            BinObjectType::Package(obj) => ObjectType::Package(Package {
+                object: self.object(obj)?,
+            }),
+            BinObjectType::Enum {
+                object,
+                cpp_type,
+                enum_flags,
+                cpp_form,
+                names,
+            } => ObjectType::Enum(Enum {
+                object: self.object(object)?,
+                cpp_type,
+                enum_flags,
+                cpp_form,
+                names,
+            }),
+            BinObjectType::ScriptStruct {
+                r#struct,
+                struct_flags,
+            } => ObjectType::ScriptStruct(ScriptStruct {
+                r#struct: self.r#struct(r#struct)?,
+                struct_flags,
+            }),
+            BinObjectType::Class {
+                r#struct,
+                class_flags,
+                class_cast_flags,
+                class_default_object,
+                instance_vtable,
+            } => ObjectType::Class(Class {
+                r#struct: self.r#struct(r#struct)?,
+                class_flags,
+                class_cast_flags,
+                class_default_object: self.opt(class_default_object)?,
+                instance_vtable,
+            }),
+            BinObjectType::Function {
+                r#struct,
+                function_flags,
+                func,
+            } => ObjectType::Function(Function {
+                r#struct: self.r#struct(r#struct)?,
+                function_flags,
+                func,
+            }),
+        })
+    }
+    fn object(&self, obj: BinObject) -> Result<Object> {
+        Ok(Object {
+            address: obj.address,
+            vtable: obj.vtable,
+            object_flags: obj.object_flags,
+            outer: self.opt(obj.outer)?,
+            class: self.string(obj.class)?,
+            children: obj
+                .children
+                .into_iter()
+                .map(|c| self.string(c))
+                .collect::<Result<_>>()?,
+            property_values: decode::<ValuesWrapper>(&obj.property_values)?,
+        })
+    }
+    fn r#struct(&self, s: BinStruct) -> Result<Struct> {
+        Ok(Struct {
+            object: self.object(s.object)?,
+            super_struct: self.opt(s.super_struct)?,
+            properties: s
+                .properties
+                .into_iter()
+                .map(|p| self.property(p))
+                .collect::<Result<_>>()?,
+            properties_size: s.properties_size,
+            min_alignment: s.min_alignment,
+            script: s.script,
+        })
+    }
+    fn property(&self, prop: BinProperty) -> Result<Property> {
+        use BinPropertyType as B;
+        use PropertyType as P;
+
+        let boxed = |prop: Box<BinProperty>| -> Result<Box<Property>> {
+            Ok(Box::new(self.property(*prop)?))
+        };
+        let r#type = match prop.r#type {
+            B::Struct(id) => P::Struct {
+                r#struct: self.string(id)?,
+            },
+            B::Str => P::Str,
+            B::Name => P::Name,
+            B::Text => P::Text,
+            B::MulticastInlineDelegate(id) => P::MulticastInlineDelegate {
+                signature_function: self.opt(id)?,
+            },
+            B::MulticastSparseDelegate(id) => P::MulticastSparseDelegate {
+                signature_function: self.opt(id)?,
+            },
+            B::MulticastDelegate(id) => P::MulticastDelegate {
+                signature_function: self.opt(id)?,
+            },
+            B::Delegate(id) => P::Delegate {
+                signature_function: self.opt(id)?,
+            },
+            B::Bool {
+                field_size,
+                byte_offset,
+                byte_mask,
+                field_mask,
+            } => P::Bool {
+                field_size,
+                byte_offset,
+                byte_mask,
+                field_mask,
+            },
+            B::Array(inner) => P::Array {
+                inner: boxed(inner)?,
+            },
+            B::Enum { container, r#enum } => P::Enum {
+                container: boxed(container)?,
+                r#enum: self.opt(r#enum)?,
+            },
+            B::Map {
+                key_prop,
+                value_prop,
+            } => P::Map {
+                key_prop: boxed(key_prop)?,
+                value_prop: boxed(value_prop)?,
+            },
+            B::Set(key_prop) => P::Set {
+                key_prop: boxed(key_prop)?,
+            },
+            B::Float => P::Float,
+            B::Double => P::Double,
+            B::Byte(id) => P::Byte {
+                r#enum: self.opt(id)?,
+            },
+            B::UInt16 => P::UInt16,
+            B::UInt32 => P::UInt32,
+            B::UInt64 => P::UInt64,
+            B::Int8 => P::Int8,
+            B::Int16 => P::Int16,
+            B::Int => P::Int,
+            B::Int64 => P::Int64,
+            B::Object(id) => P::Object {
+                property_class: self.string(id)?,
+            },
+            B::Class {
+                property_class,
+                meta_class,
+            } => P::Class {
+                property_class: self.string(property_class)?,
+                meta_class: self.string(meta_class)?,
+            },
+            B::WeakObject(id) => P::WeakObject {
+                property_class: self.string(id)?,
+            },
+            B::SoftObject(id) => P::SoftObject {
+                property_class: self.string(id)?,
+            },
+            B::SoftClass {
+                property_class,
+                meta_class,
+            } => P::SoftClass {
+                property_class: self.string(property_class)?,
+                meta_class: self.string(meta_class)?,
+            },
+            B::LazyObject(id) => P::LazyObject {
+                property_class: self.string(id)?,
+            },
+            B::Interface(id) => P::Interface {
+                interface_class: self.string(id)?,
+            },
+            B::FieldPath(id) => P::FieldPath {
+                property_class: self.opt(id)?,
+            },
+            B::Optional(inner) => P::Optional {
+                inner: boxed(inner)?,
+            },
+            B::Utf8Str => P::Utf8Str,
+            B::AnsiStr => P::AnsiStr,
+        };
+        Ok(Property {
+            address: prop.address,
+            name: prop.name,
+            offset: prop.offset,
+            array_dim: prop.array_dim,
+            size: prop.size,
+            r#type,
+            flags: prop.flags,
+        })
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes(
+        data.get(offset..offset + 4)
+            .context("read out of bounds")?
+            .try_into()?,
+    ))
+}
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    Ok(u64::from_le_bytes(
+        data.get(offset..offset + 8)
+            .context("read out of bounds")?
+            .try_into()?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use ordermap::OrderMap;
+
+    use super::*;
+    use crate::{ETextFlag, ETextHistoryType, EngineVersion, PropertyValue, TextPropertyValue};
+
+    fn sample() -> Jmap {
+        let thing = ObjectType::Class(Class {
+            r#struct: Struct {
+                object: Object::new("/Script/CoreUObject.Class", Some("/Script/Test".into())),
+                super_struct: Some("/Script/CoreUObject.Object".into()),
+                properties: vec![
+                    Property {
+                        offset: 0x28,
+                        size: 4,
+                        ..Property::new("Health", PropertyType::Float)
+                    },
+                    Property {
+                        offset: 0x30,
+                        size: 8,
+                        ..Property::new(
+                            "Owner",
+                            PropertyType::Object {
+                                property_class: "/Script/Test.Thing".into(),
+                            },
+                        )
+                    },
+                ],
+                properties_size: 0x38,
+                min_alignment: 8,
+                script: vec![1, 2, 3],
+            },
+            class_flags: EClassFlags::CLASS_Native,
+            class_cast_flags: EClassCastFlags::empty(),
+            class_default_object: Some("/Script/Test.Default__Thing".into()),
+            instance_vtable: Some(Address(0x2000)),
+        });
+        let cdo = ObjectType::Object(Object {
+            address: Address(0x1000),
+            property_values: OrderMap::from_iter([
+                ("Health".to_string(), PropertyValue::Float(1.5.into())),
+                ("Owner".to_string(), PropertyValue::Object(None)),
+                (
+                    "Label".to_string(),
+                    PropertyValue::Text(Box::new(TextPropertyValue {
+                        history: Some(ETextHistoryType::None),
+                        flags: ETextFlag::CultureInvariant,
+                        namespace: None,
+                        key: None,
+                        source_string: None,
+                        display_string: Some("Thing".into()),
+                    })),
+                ),
+            ])
+            .into(),
+            ..Object::new("/Script/Test.Thing", Some("/Script/Test".into()))
+        });
+        let mut package = Object::new("/Script/CoreUObject.Package", None);
+        package.children = [
+            "/Script/Test.Default__Thing".to_string(),
+            "/Script/Test.Thing".to_string(),
+        ]
+        .into();
+        Jmap {
+            metadata: Some(Metadata {
+                tool: "test".into(),
+                timestamp: "2024-01-01T00:00:00Z".into(),
+                source: "test.exe".into(),
+                engine_version: EngineVersion { major: 5, minor: 4 },
+                build_change_list: None,
+            }),
+            image_base_address: Address(0x140000000),
+            objects: BTreeMap::from_iter([
+                (
+                    "/Script/Test".to_string(),
+                    ObjectType::Package(Package { object: package }),
+                ),
+                ("/Script/Test.Thing".to_string(), thing),
+                ("/Script/Test.Default__Thing".to_string(), cdo),
+            ]),
+            vtables: BTreeMap::from_iter([(Address(0x2000), vec![Address(0x1400)])]),
+            names: Some(BTreeMap::from_iter([(0, "None".into())])),
+            diagnostics: vec![Diagnostic {
+                index: Some(3),
+                path: None,
+                property: Some("Broken".into()),
+                error: vec!["failed".into()],
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() -> Result<()> {
+        let jmap = sample();
+        let mut data = vec![];
+        write_binary(&jmap, &mut data)?;
+        assert!(is_binary(&data));
+
+        let binary = JmapBinary::new(data)?;
+        assert_eq!(binary.len(), 3);
+        assert!(binary.paths().eq(jmap.objects.keys().map(String::as_str)));
+        assert_eq!(
+            serde_json::to_value(binary.to_jmap()?)?,
+            serde_json::to_value(&jmap)?
+        );
+
+        for (path, obj) in &jmap.objects {
+            let decoded = binary.get(path)?.context("object missing")?;
+            assert_eq!(serde_json::to_value(decoded)?, serde_json::to_value(obj)?);
+        }
+        assert!(binary.get("/Script/Test.Missing")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_interns_paths() -> Result<()> {
+        let mut data = vec![];
+        write_binary(&sample(), &mut data)?;
+        // object path, CDO class and property class share one string
+        let path = b"/Script/Test.Thing";
+        let count = data.windows(path.len()).filter(|w| w == path).count();
+        assert_eq!(count, 1);
+        // field names are not encoded
+        let field = b"properties_size";
+        assert!(!data.windows(field.len()).any(|w| w == field));
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_rejects_other_versions() -> Result<()> {
+        let mut data = vec![];
+        write_binary(&sample(), &mut data)?;
+        data[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        let err = JmapBinary::new(data).err().context("expected an error")?;
+        assert!(err.to_string().contains("unsupported binary jmap version"));
+
+        let mut data = vec![];
+        write_binary(
+            &Jmap {
+                format_version: FORMAT_VERSION + 1,
+                ..sample()
+            },
+            &mut data,
+        )?;
+        let err = JmapBinary::new(data).err().context("expected an error")?;
+        assert!(err.to_string().contains("format version"));
+        Ok(())
+    }
+}
@@ -0,0 +1,791 @@
+//! Semantic comparison of two dumps, e.g. of consecutive game patches, and JSON Patch
+//! (RFC 6902) generation for machine-readable output
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{EPropertyFlags, Jmap, ObjectType, Property, PropertyType, Struct};
+
+#[derive(Debug, Clone, Default)]
+pub struct JmapDiff {
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Path of the object, or name of the struct or enum when comparing usmaps
+    pub path: String,
+    /// Property, parameter, enum name or default value within the object, if any
+    pub member: Option<String>,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified {
+        /// Name of the changed field e.g. "offset", "super_struct", "value"
+        field: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl JmapDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl std::fmt::Display for JmapDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            let target = match &change.member {
+                Some(member) => format!("{}:{member}", change.path),
+                None => change.path.clone(),
+            };
+            match &change.kind {
+                ChangeKind::Added => writeln!(f, "+ {target}")?,
+                ChangeKind::Removed => writeln!(f, "- {target}")?,
+                ChangeKind::Modified { field, old, new } => writeln!(
+                    f,
+                    "~ {target} {field}: {} -> {}",
+                    display_value(old),
+                    display_value(new)
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "None".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Compare two dumps. Changes are ordered by object path.
+pub fn diff(old: &Jmap, new: &Jmap) -> JmapDiff {
+    let mut d = Differ::default();
+
+    let paths: BTreeSet<&String> = old.objects.keys().chain(new.objects.keys()).collect();
+    for path in paths {
+        match (old.objects.get(path), new.objects.get(path)) {
+            (Some(_), None) => d.push(path, None, ChangeKind::Removed),
+            (None, Some(_)) => d.push(path, None, ChangeKind::Added),
+            (Some(old), Some(new)) => d.object(path, old, new),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    d.finish()
+}
+
+/// Collects [`Change`]s, also used to compare other formats (e.g. usmap) the same way
+#[derive(Default)]
+pub struct Differ {
+    changes: Vec<Change>,
+}
+impl Differ {
+    pub fn push(&mut self, path: &str, member: Option<&str>, kind: ChangeKind) {
+        self.changes.push(Change {
+            path: path.to_string(),
+            member: member.map(str::to_string),
+            kind,
+        });
+    }
+    /// Record `field` as modified if `old` and `new` differ
+    pub fn modified<T: Serialize + PartialEq>(
+        &mut self,
+        path: &str,
+        member: Option<&str>,
+        field: &str,
+        old: T,
+        new: T,
+    ) {
+        if old != new {
+            self.push(
+                path,
+                member,
+                ChangeKind::Modified {
+                    field: field.to_string(),
+                    old: serde_json::to_value(old).unwrap(),
+                    new: serde_json::to_value(new).unwrap(),
+                },
+            );
+        }
+    }
+    pub fn finish(self) -> JmapDiff {
+        JmapDiff {
+            changes: self.changes,
+        }
+    }
+
+    fn object(&mut self, path: &str, old: &ObjectType, new: &ObjectType) {
+        self.modified(path, None, "type", type_name(old), type_name(new));
+        self.modified(
+            path,
+            None,
+            "class",
+            &old.get_object().class,
+            &new.get_object().class,
+        );
+
+        match (old, new) {
+            (ObjectType::ScriptStruct(old), ObjectType::ScriptStruct(new)) => {
+                self.modified(
+                    path,
+                    None,
+                    "struct_flags",
+                    hex(old.struct_flags.bits()),
+                    hex(new.struct_flags.bits()),
+                );
+                self.r#struct(path, &old.r#struct, &new.r#struct);
+            }
+            (ObjectType::Class(old), ObjectType::Class(new)) => {
+                self.modified(
+                    path,
+                    None,
+                    "class_flags",
+                    hex(old.class_flags.bits()),
+                    hex(new.class_flags.bits()),
+                );
+                self.r#struct(path, &old.r#struct, &new.r#struct);
+            }
+            (ObjectType::Function(old), ObjectType::Function(new)) => {
+                self.modified(
+                    path,
+                    None,
+                    "function_flags",
+                    hex(old.function_flags.bits()),
+                    hex(new.function_flags.bits()),
+                );
+                self.modified(
+                    path,
+                    None,
+                    "signature",
+                    signature(&old.r#struct),
+                    signature(&new.r#struct),
+                );
+            }
+            (ObjectType::Enum(old), ObjectType::Enum(new)) => {
+                let old_names: BTreeMap<_, _> = old.names.iter().cloned().collect();
+                let new_names: BTreeMap<_, _> = new.names.iter().cloned().collect();
+                for name in old_names
+                    .keys()
+                    .chain(new_names.keys())
+                    .collect::<BTreeSet<_>>()
+                {
+                    let member = Some(name.as_str());
+                    match (old_names.get(name), new_names.get(name)) {
+                        (Some(_), None) => self.push(path, member, ChangeKind::Removed),
+                        (None, Some(_)) => self.push(path, member, ChangeKind::Added),
+                        (Some(old), Some(new)) => self.modified(path, member, "value", old, new),
+                        (None, None) => unreachable!(),
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.values(path, old, new);
+    }
+
+    fn r#struct(&mut self, path: &str, old: &Struct, new: &Struct) {
+        self.modified(
+            path,
+            None,
+            "super_struct",
+            &old.super_struct,
+            &new.super_struct,
+        );
+        self.modified(path, None, "size", old.properties_size, new.properties_size);
+        self.modified(
+            path,
+            None,
+            "min_alignment",
+            old.min_alignment,
+            new.min_alignment,
+        );
+
+        let old_props: BTreeMap<_, _> = old.properties.iter().map(|p| (&p.name, p)).collect();
+        let new_props: BTreeMap<_, _> = new.properties.iter().map(|p| (&p.name, p)).collect();
+        // report in declaration order of the new struct, removed properties last
+        let names = new
+            .properties
+            .iter()
+            .chain(&old.properties)
+            .map(|p| &p.name)
+            .collect::<ordermap::OrderSet<_>>();
+        for name in names {
+            let member = Some(name.as_str());
+            match (old_props.get(name), new_props.get(name)) {
+                (Some(_), None) => self.push(path, member, ChangeKind::Removed),
+                (None, Some(_)) => self.push(path, member, ChangeKind::Added),
+                (Some(old), Some(new)) => self.property(path, name, old, new),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+
+    fn property(&mut self, path: &str, name: &str, old: &Property, new: &Property) {
+        let member = Some(name);
+        self.modified(path, member, "offset", hex(old.offset), hex(new.offset));
+        self.modified(path, member, "size", old.size, new.size);
+        self.modified(path, member, "array_dim", old.array_dim, new.array_dim);
+        self.modified(
+            path,
+            member,
+            "type",
+            type_string(&old.r#type),
+            type_string(&new.r#type),
+        );
+        self.modified(
+            path,
+            member,
+            "flags",
+            hex(old.flags.bits()),
+            hex(new.flags.bits()),
+        );
+    }
+
+    /// Compare reflected property values, e.g. of class default objects
+    fn values(&mut self, path: &str, old: &ObjectType, new: &ObjectType) {
+        let to_map =
+            |obj: &ObjectType| match serde_json::to_value(&obj.get_object().property_values) {
+                Ok(Value::Object(map)) => map,
+                _ => Default::default(),
+            };
+        let old = to_map(old);
+        let new = to_map(new);
+        for key in old.keys().chain(new.keys()).collect::<BTreeSet<_>>() {
+            let member = Some(key.as_str());
+            match (old.get(key), new.get(key)) {
+                (Some(_), None) => self.push(path, member, ChangeKind::Removed),
+                (None, Some(_)) => self.push(path, member, ChangeKind::Added),
+                (Some(old), Some(new)) => self.modified(path, member, "value", old, new),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+}
+
+/// JSON Patch (RFC 6902) operation, `path` is a JSON Pointer (RFC 6901) into the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+/// JSON Patch turning `old` into `new`, e.g. the JSON of two dumps or usmaps. Array elements
+/// are matched by `name` where they have one, so an inserted property is a single `add` rather
+/// than a rewrite of every property after it.
+pub fn json_patch(old: &Value, new: &Value) -> Vec<PatchOperation> {
+    let mut patch = vec![];
+    patch_value(&mut patch, String::new(), old, new);
+    patch
+}
+
+fn patch_value(patch: &mut Vec<PatchOperation>, path: String, old: &Value, new: &Value) {
+    match (old, new) {
+        _ if old == new => {}
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old) in old {
+                let path = format!("{path}/{}", escape_pointer(key));
+                match new.get(key) {
+                    Some(new) => patch_value(patch, path, old, new),
+                    None => patch.push(PatchOperation::Remove { path }),
+                }
+            }
+            for (key, value) in new {
+                if !old.contains_key(key) {
+                    patch.push(PatchOperation::Add {
+                        path: format!("{path}/{}", escape_pointer(key)),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => patch_array(patch, &path, old, new),
+        _ => patch.push(PatchOperation::Replace {
+            path,
+            value: new.clone(),
+        }),
+    }
+}
+
+enum Edit {
+    Keep(usize, usize),
+    Remove,
+    Add(usize),
+}
+
+/// Above this many element pairs the differing middle of an array is replaced as a whole
+const MAX_ALIGN: usize = 1 << 20;
+
+fn patch_array(patch: &mut Vec<PatchOperation>, path: &str, old: &[Value], new: &[Value]) {
+    let same = |i: usize, j: usize| element_key(&old[i]) == element_key(&new[j]);
+
+    let prefix = (0..old.len().min(new.len()))
+        .take_while(|&i| same(i, i))
+        .count();
+    let suffix = (1..=(old.len() - prefix).min(new.len() - prefix))
+        .take_while(|&i| same(old.len() - i, new.len() - i))
+        .count();
+    let (n, m) = (old.len() - prefix - suffix, new.len() - prefix - suffix);
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    if n * m <= MAX_ALIGN {
+        // longest common subsequence of the middle, lcs[i][j] for old[i..] and new[j..]
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[at(i, j)] = if same(prefix + i, prefix + j) {
+                    lcs[at(i + 1, j + 1)] + 1
+                } else {
+                    lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if same(prefix + i, prefix + j) {
+                edits.push(Edit::Keep(prefix + i, prefix + j));
+                (i, j) = (i + 1, j + 1);
+            } else if lcs[at(i + 1, j)] >= lcs[at(i, j + 1)] {
+                edits.push(Edit::Remove);
+                i += 1;
+            } else {
+                edits.push(Edit::Add(prefix + j));
+                j += 1;
+            }
+        }
+        edits.extend((i..n).map(|_| Edit::Remove));
+        edits.extend((j..m).map(|j| Edit::Add(prefix + j)));
+    } else {
+        edits.extend((0..n).map(|_| Edit::Remove));
+        edits.extend((0..m).map(|j| Edit::Add(prefix + j)));
+    }
+    edits.extend((0..suffix).map(|i| Edit::Keep(prefix + n + i, prefix + m + i)));
+
+    // operations apply in order so indices are positions in the partially patched array
+    let mut index = 0;
+    for edit in edits {
+        match edit {
+            Edit::Keep(i, j) => {
+                patch_value(patch, format!("{path}/{index}"), &old[i], &new[j]);
+                index += 1;
+            }
+            Edit::Remove => patch.push(PatchOperation::Remove {
+                path: format!("{path}/{index}"),
+            }),
+            Edit::Add(j) => {
+                patch.push(PatchOperation::Add {
+                    path: format!("{path}/{index}"),
+                    value: new[j].clone(),
+                });
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Identity of an array element: the name of named elements such as properties, otherwise the
+/// whole value
+fn element_key(value: &Value) -> &Value {
+    value
+        .get("name")
+        .filter(|name| name.is_string())
+        .unwrap_or(value)
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn type_name(obj: &ObjectType) -> &'static str {
+    match obj {
+        ObjectType::Object(_) => "Object",
+        ObjectType::Package(_) => "Package",
+        ObjectType::Enum(_) => "Enum",
+        ObjectType::ScriptStruct(_) => "ScriptStruct",
+        ObjectType::Class(_) => "Class",
+        ObjectType::Function(_) => "Function",
+    }
+}
+
+fn hex(value: impl std::fmt::LowerHex) -> String {
+    format!("0x{value:x}")
+}
+
+/// Parameters in declaration order followed by the return type e.g.
+/// `(IntProperty A, out StructProperty</Script/CoreUObject.Vector> B) -> BoolProperty`
+fn signature(func: &Struct) -> String {
+    let mut params = vec![];
+    let mut ret = None;
+    for prop in &func.properties {
+        if !prop.flags.contains(EPropertyFlags::CPF_Parm) {
+            continue;
+        }
+        if prop.flags.contains(EPropertyFlags::CPF_ReturnParm) {
+            ret = Some(type_string(&prop.r#type));
+            continue;
+        }
+        let mut param = String::new();
+        if prop.flags.contains(EPropertyFlags::CPF_ConstParm) {
+            param.push_str("const ");
+        }
+        if prop.flags.contains(EPropertyFlags::CPF_OutParm) {
+            param.push_str("out ");
+        }
+        write!(param, "{} {}", type_string(&prop.r#type), prop.name).unwrap();
+        params.push(param);
+    }
+    let mut signature = format!("({})", params.join(", "));
+    if let Some(ret) = ret {
+        write!(signature, " -> {ret}").unwrap();
+    }
+    signature
+}
+
+/// Compact description of a property type including referenced types
+pub fn type_string(r#type: &PropertyType) -> String {
+    use PropertyType as PT;
+    let inner = |p: &Property| type_string(&p.r#type);
+    let opt = |s: &Option<String>| s.as_deref().unwrap_or("None").to_string();
+    match r#type {
+        PT::Struct { r#struct } => format!("StructProperty<{struct}>"),
+        PT::Str => "StrProperty".into(),
+        PT::Name => "NameProperty".into(),
+        PT::Text => "TextProperty".into(),
+        PT::MulticastInlineDelegate { signature_function } => {
+            format!(
+                "MulticastInlineDelegateProperty<{}>",
+                opt(signature_function)
+            )
+        }
+        PT::MulticastSparseDelegate { signature_function } => {
+            format!(
+                "MulticastSparseDelegateProperty<{}>",
+                opt(signature_function)
+            )
+        }
+        PT::MulticastDelegate { signature_function } => {
+            format!("MulticastDelegateProperty<{}>", opt(signature_function))
+        }
+        PT::Delegate { signature_function } => {
+            format!("DelegateProperty<{}>", opt(signature_function))
+        }
+        PT::Bool {
+            field_size,
+            byte_offset,
+            byte_mask,
+            field_mask,
+        } => format!(
+            "BoolProperty<size={field_size}, offset={byte_offset}, byte_mask=0x{byte_mask:x}, field_mask=0x{field_mask:x}>"
+        ),
+        PT::Array { inner: i } => format!("ArrayProperty<{}>", inner(i)),
+        PT::Enum { container, r#enum } => {
+            format!("EnumProperty<{}, {}>", inner(container), opt(r#enum))
+        }
+        PT::Map {
+            key_prop,
+            value_prop,
+        } => format!("MapProperty<{}, {}>", inner(key_prop), inner(value_prop)),
+        PT::Set { key_prop } => format!("SetProperty<{}>", inner(key_prop)),
+        PT::Float => "FloatProperty".into(),
+        PT::Double => "DoubleProperty".into(),
+        PT::Byte { r#enum } => match r#enum {
+            Some(e) => format!("ByteProperty<{e}>"),
+            None => "ByteProperty".into(),
+        },
+        PT::UInt16 => "UInt16Property".into(),
+        PT::UInt32 => "UInt32Property".into(),
+        PT::UInt64 => "UInt64Property".into(),
+        PT::Int8 => "Int8Property".into(),
+        PT::Int16 => "Int16Property".into(),
+        PT::Int => "IntProperty".into(),
+        PT::Int64 => "Int64Property".into(),
+        PT::Object { property_class } => format!("ObjectProperty<{property_class}>"),
+        PT::Class {
+            property_class,
+            meta_class,
+        } => format!("ClassProperty<{property_class}, {meta_class}>"),
+        PT::WeakObject { property_class } => format!("WeakObjectProperty<{property_class}>"),
+        PT::SoftObject { property_class } => format!("SoftObjectProperty<{property_class}>"),
+        PT::SoftClass {
+            property_class,
+            meta_class,
+        } => format!("SoftClassProperty<{property_class}, {meta_class}>"),
+        PT::LazyObject { property_class } => format!("LazyObjectProperty<{property_class}>"),
+        PT::Interface { interface_class } => format!("InterfaceProperty<{interface_class}>"),
+        PT::FieldPath { property_class } => {
+            format!("FieldPathProperty<{}>", opt(property_class))
+        }
+        PT::Optional { inner: i } => format!("OptionalProperty<{}>", inner(i)),
+        PT::Utf8Str => "Utf8StrProperty".into(),
+        PT::AnsiStr => "AnsiStrProperty".into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ordermap::OrderMap;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{class, jmap, prop};
+    use crate::{Object, PropertyValue};
+
+    /// Class deriving from Object
+    fn thing(properties: Vec<Property>) -> ObjectType {
+        ObjectType::Class(class(Some("/Script/CoreUObject.Object"), properties))
+    }
+
+    fn instance<const N: usize>(values: [(&str, PropertyValue); N]) -> ObjectType {
+        let values = values.into_iter().map(|(k, v)| (k.to_string(), v));
+        ObjectType::Object(Object {
+            property_values: OrderMap::from_iter(values).into(),
+            ..Object::new("/Script/Test.Thing", Some("/Script/Test".into()))
+        })
+    }
+
+    fn change(path: &str, member: Option<&str>, kind: ChangeKind) -> Change {
+        Change {
+            path: path.into(),
+            member: member.map(str::to_string),
+            kind,
+        }
+    }
+
+    fn modified(field: &str, old: impl Into<Value>, new: impl Into<Value>) -> ChangeKind {
+        ChangeKind::Modified {
+            field: field.into(),
+            old: old.into(),
+            new: new.into(),
+        }
+    }
+
+    #[test]
+    fn test_diff_objects() {
+        let old = jmap([
+            ("/Script/Test.A", instance([])),
+            ("/Script/Test.B", instance([])),
+        ]);
+        let new = jmap([
+            ("/Script/Test.B", instance([])),
+            ("/Script/Test.C", instance([])),
+        ]);
+        assert_eq!(
+            diff(&old, &new).changes,
+            [
+                change("/Script/Test.A", None, ChangeKind::Removed),
+                change("/Script/Test.C", None, ChangeKind::Added),
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_properties() {
+        let old = jmap([(
+            "/Script/Test.Thing",
+            thing(vec![
+                prop("X", 0, 4, PropertyType::Int),
+                prop("Y", 4, 4, PropertyType::Int),
+                prop("W", 8, 4, PropertyType::Int),
+            ]),
+        )]);
+        let new = jmap([(
+            "/Script/Test.Thing",
+            thing(vec![
+                prop("Z", 0, 4, PropertyType::Float),
+                prop("X", 4, 4, PropertyType::Int),
+                prop("W", 8, 4, PropertyType::Float),
+            ]),
+        )]);
+        let path = "/Script/Test.Thing";
+        // declaration order of the new struct, removed properties last
+        assert_eq!(
+            diff(&old, &new).changes,
+            [
+                change(path, Some("Z"), ChangeKind::Added),
+                change(path, Some("X"), modified("offset", "0x0", "0x4")),
+                change(
+                    path,
+                    Some("W"),
+                    modified("type", "IntProperty", "FloatProperty")
+                ),
+                change(path, Some("Y"), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let old = jmap([(
+            "/Script/Test.Default__Thing",
+            instance([
+                ("Health", PropertyValue::Int(1)),
+                ("Old", PropertyValue::Bool(true)),
+            ]),
+        )]);
+        let new = jmap([(
+            "/Script/Test.Default__Thing",
+            instance([
+                ("Health", PropertyValue::Int(2)),
+                ("New", PropertyValue::Str("a".into())),
+            ]),
+        )]);
+        let path = "/Script/Test.Default__Thing";
+        assert_eq!(
+            diff(&old, &new).changes,
+            [
+                change(path, Some("Health"), modified("value", 1, 2)),
+                change(path, Some("New"), ChangeKind::Added),
+                change(path, Some("Old"), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_output() -> serde_json::Result<()> {
+        let diff = JmapDiff {
+            changes: vec![
+                change("/Script/Test.A", None, ChangeKind::Added),
+                change("/Script/Test.B", Some("X"), ChangeKind::Removed),
+                change(
+                    "/Script/Test.C",
+                    Some("Y"),
+                    modified("offset", "0x0", "0x4"),
+                ),
+                change(
+                    "/Script/Test.D",
+                    None,
+                    modified("super_struct", "/Script/Test.E", Value::Null),
+                ),
+            ],
+        };
+        assert_eq!(
+            diff.to_string(),
+            "+ /Script/Test.A\n\
+             - /Script/Test.B:X\n\
+             ~ /Script/Test.C:Y offset: 0x0 -> 0x4\n\
+             ~ /Script/Test.D super_struct: /Script/Test.E -> None\n"
+        );
+        Ok(())
+    }
+
+    /// Apply a patch the way RFC 6902 describes it
+    fn apply(doc: &mut Value, patch: &[PatchOperation]) {
+        for op in patch {
+            let (path, value) = match op {
+                PatchOperation::Add { path, value } | PatchOperation::Replace { path, value } => {
+                    (path, Some(value.clone()))
+                }
+                PatchOperation::Remove { path } => (path, None),
+            };
+            let (parent, last) = path.rsplit_once('/').unwrap();
+            let last = last.replace("~1", "/").replace("~0", "~");
+            match (op, doc.pointer_mut(parent).unwrap()) {
+                (PatchOperation::Add { .. }, Value::Array(a)) => {
+                    a.insert(last.parse().unwrap(), value.unwrap())
+                }
+                (PatchOperation::Remove { .. }, Value::Array(a)) => {
+                    a.remove(last.parse().unwrap());
+                }
+                (_, Value::Array(a)) => a[last.parse::<usize>().unwrap()] = value.unwrap(),
+                (PatchOperation::Remove { .. }, Value::Object(o)) => {
+                    o.remove(&last);
+                }
+                (_, Value::Object(o)) => {
+                    o.insert(last, value.unwrap());
+                }
+                _ => panic!("{path} does not point into an array or object"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_patch() -> serde_json::Result<()> {
+        let old = serde_json::to_value(jmap([
+            (
+                "/Script/Test.Thing",
+                thing(vec![
+                    prop("X", 0, 4, PropertyType::Int),
+                    prop("Y", 4, 4, PropertyType::Int),
+                    prop("W", 8, 4, PropertyType::Int),
+                ]),
+            ),
+            (
+                "/Script/Test.Default__Thing",
+                instance([("Health", PropertyValue::Int(1))]),
+            ),
+            ("/Script/Test.Old", instance([])),
+        ]))?;
+        let new = serde_json::to_value(jmap([
+            (
+                "/Script/Test.Thing",
+                thing(vec![
+                    prop("Z", 0, 4, PropertyType::Float),
+                    prop("X", 4, 4, PropertyType::Int),
+                    prop("W", 8, 4, PropertyType::Float),
+                ]),
+            ),
+            (
+                "/Script/Test.Default__Thing",
+                instance([("Health", PropertyValue::Int(2))]),
+            ),
+        ]))?;
+
+        let patch = json_patch(&old, &new);
+        let properties = "/objects/~1Script~1Test.Thing/properties";
+        assert_eq!(
+            patch,
+            [
+                PatchOperation::Replace {
+                    path: "/objects/~1Script~1Test.Default__Thing/property_values/Health".into(),
+                    value: json!(2),
+                },
+                PatchOperation::Remove {
+                    path: "/objects/~1Script~1Test.Old".into(),
+                },
+                PatchOperation::Add {
+                    path: format!("{properties}/0"),
+                    value: serde_json::to_value(prop("Z", 0, 4, PropertyType::Float))?,
+                },
+                PatchOperation::Replace {
+                    path: format!("{properties}/1/offset"),
+                    value: json!(4),
+                },
+                PatchOperation::Remove {
+                    path: format!("{properties}/2"),
+                },
+                PatchOperation::Replace {
+                    path: format!("{properties}/2/type"),
+                    value: json!("FloatProperty"),
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&patch[1])?,
+            json!({ "op": "remove", "path": "/objects/~1Script~1Test.Old" })
+        );
+
+        let mut patched = old.clone();
+        apply(&mut patched, &patch);
+        assert_eq!(patched, new);
+        assert!(json_patch(&old, &old).is_empty());
+        Ok(())
+    }
+}
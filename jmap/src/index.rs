@@ -0,0 +1,498 @@
+//! Lookup tables over a [`Jmap`] for hierarchy queries: names, super and sub classes,
+//! inherited properties, functions, instances and class default objects.
+//!
+//! All tables are built once by [`JmapIndex::new`] so queries never scan all objects.
+
+use std::collections::HashMap;
+
+use crate::{Class, EObjectFlags, Function, Jmap, ObjectType, Property, Struct};
+
+/// Object path split into its parts, e.g. `/Game/Maps/Map.Map:PersistentLevel.Actor` is
+/// package `/Game/Maps/Map`, object `Map` and subobject `PersistentLevel.Actor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectPath<'a> {
+    pub package: &'a str,
+    /// Top level object within the package, None for the package itself
+    pub object: Option<&'a str>,
+    /// Everything after the subobject delimiter `:`, may itself contain `.`
+    pub subobject: Option<&'a str>,
+}
+impl<'a> ObjectPath<'a> {
+    pub fn parse(path: &'a str) -> Self {
+        let (package, rest) = match path.split_once('.') {
+            Some((package, rest)) => (package, Some(rest)),
+            None => (path, None),
+        };
+        let (object, subobject) = match rest.map(|rest| rest.split_once(':')) {
+            Some(Some((object, subobject))) => (Some(object), Some(subobject)),
+            Some(None) => (rest, None),
+            None => (None, None),
+        };
+        Self {
+            package,
+            object,
+            subobject,
+        }
+    }
+    /// Short name of the object e.g. `Actor` for `/Script/Engine.Actor`
+    pub fn name(&self) -> &'a str {
+        let last = self.subobject.or(self.object).unwrap_or(self.package);
+        last.rsplit(['/', '.', ':']).next().unwrap()
+    }
+    pub fn is_package(&self) -> bool {
+        self.object.is_none()
+    }
+}
+impl std::fmt::Display for ObjectPath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.package)?;
+        if let Some(object) = self.object {
+            write!(f, ".{object}")?;
+        }
+        if let Some(subobject) = self.subobject {
+            write!(f, ":{subobject}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Property of a struct or one of its super structs
+#[derive(Debug, Clone, Copy)]
+pub struct InheritedProperty<'a> {
+    /// Path of the struct declaring the property
+    pub owner: &'a str,
+    pub property: &'a Property,
+    /// Offset from the start of the queried struct. Unreal stores property offsets relative to
+    /// the outermost struct already, so this equals `property.offset`.
+    pub offset: usize,
+}
+impl InheritedProperty<'_> {
+    /// Offset of element `index` of a static array
+    pub fn element_offset(&self, index: usize) -> usize {
+        self.offset + index * self.property.size
+    }
+}
+
+pub struct JmapIndex<'a> {
+    jmap: &'a Jmap,
+    by_name: HashMap<&'a str, Vec<&'a str>>,
+    /// Direct subclasses (or sub structs) by super struct path
+    subclasses: HashMap<&'a str, Vec<&'a str>>,
+    /// Objects by class path
+    instances: HashMap<&'a str, Vec<&'a str>>,
+    cdos: HashMap<&'a str, &'a str>,
+}
+
+impl Jmap {
+    pub fn index(&self) -> JmapIndex<'_> {
+        JmapIndex::new(self)
+    }
+}
+
+impl<'a> JmapIndex<'a> {
+    pub fn new(jmap: &'a Jmap) -> Self {
+        let mut by_name: HashMap<_, Vec<_>> = HashMap::new();
+        let mut subclasses: HashMap<_, Vec<_>> = HashMap::new();
+        let mut instances: HashMap<_, Vec<_>> = HashMap::new();
+        let mut cdos = HashMap::new();
+
+        for (path, obj) in &jmap.objects {
+            let path = path.as_str();
+            by_name
+                .entry(ObjectPath::parse(path).name())
+                .or_default()
+                .push(path);
+
+            let object = obj.get_object();
+            instances
+                .entry(object.class.as_str())
+                .or_default()
+                .push(path);
+
+            if let Some(super_struct) = obj.get_struct().and_then(|s| s.super_struct.as_deref()) {
+                subclasses.entry(super_struct).or_default().push(path);
+            }
+            if let Some(cdo) = obj
+                .get_class()
+                .and_then(|c| c.class_default_object.as_deref())
+            {
+                cdos.insert(path, cdo);
+            }
+        }
+        // classes which did not record their CDO
+        for (path, obj) in &jmap.objects {
+            let object = obj.get_object();
+            if object
+                .object_flags
+                .contains(EObjectFlags::RF_ClassDefaultObject)
+            {
+                cdos.entry(object.class.as_str()).or_insert(path.as_str());
+            }
+        }
+
+        Self {
+            jmap,
+            by_name,
+            subclasses,
+            instances,
+            cdos,
+        }
+    }
+
+    pub fn jmap(&self) -> &'a Jmap {
+        self.jmap
+    }
+    pub fn get(&self, path: &str) -> Option<&'a ObjectType> {
+        self.jmap.objects.get(path)
+    }
+    pub fn get_struct(&self, path: &str) -> Option<&'a Struct> {
+        self.get(path).and_then(|obj| obj.get_struct())
+    }
+    pub fn get_class(&self, path: &str) -> Option<&'a Class> {
+        self.get(path).and_then(|obj| obj.get_class())
+    }
+
+    /// Paths of all objects with the given short name, in path order
+    pub fn find_by_name(&self, name: &str) -> &[&'a str] {
+        self.by_name.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// `path` followed by its super structs, closest first. Stops at the first super struct
+    /// missing from the jmap.
+    pub fn super_chain(&self, path: &str) -> SuperChain<'a> {
+        SuperChain {
+            jmap: self.jmap,
+            next: self
+                .jmap
+                .objects
+                .get_key_value(path)
+                .map(|(p, _)| p.as_str()),
+            remaining: self.jmap.objects.len(),
+        }
+    }
+    /// Whether `path` is `base` or derives from it
+    pub fn is_a(&self, path: &str, base: &str) -> bool {
+        self.super_chain(path).any(|(p, _)| p == base)
+    }
+
+    /// Structs whose super struct is `path`
+    pub fn direct_subclasses(&self, path: &str) -> &[&'a str] {
+        self.subclasses.get(path).map_or(&[], Vec::as_slice)
+    }
+    /// All structs deriving from `path` directly or indirectly, depth first
+    pub fn subclasses(&self, path: &str) -> Subclasses<'_, 'a> {
+        Subclasses {
+            index: self,
+            stack: self.direct_subclasses(path).iter().rev().copied().collect(),
+            remaining: self.jmap.objects.len(),
+        }
+    }
+
+    /// Properties of `path` including inherited ones, base-most struct first which matches
+    /// memory layout
+    pub fn inherited_properties(&self, path: &str) -> impl Iterator<Item = InheritedProperty<'a>> {
+        let mut chain: Vec<_> = self.super_chain(path).collect();
+        chain.reverse();
+        chain.into_iter().flat_map(|(owner, s)| {
+            s.properties.iter().map(move |property| InheritedProperty {
+                owner,
+                property,
+                offset: property.offset,
+            })
+        })
+    }
+
+    /// Functions declared by `path` (not inherited)
+    pub fn functions(&self, path: &str) -> impl Iterator<Item = (&'a str, &'a Function)> {
+        let jmap = self.jmap;
+        self.get(path)
+            .into_iter()
+            .flat_map(|obj| &obj.get_object().children)
+            .filter_map(move |child| match jmap.objects.get(child) {
+                Some(ObjectType::Function(func)) => Some((child.as_str(), func)),
+                _ => None,
+            })
+    }
+
+    /// Objects whose class is exactly `class`, in path order. Combine with
+    /// [`subclasses`](Self::subclasses) to include instances of derived classes.
+    pub fn instances(&self, class: &str) -> &[&'a str] {
+        self.instances.get(class).map_or(&[], Vec::as_slice)
+    }
+
+    /// Class default object of `class`
+    pub fn cdo(&self, class: &str) -> Option<&'a str> {
+        self.cdos.get(class).copied()
+    }
+}
+
+pub struct SuperChain<'a> {
+    jmap: &'a Jmap,
+    next: Option<&'a str>,
+    /// bounded in case of cyclic super structs
+    remaining: usize,
+}
+impl<'a> Iterator for SuperChain<'a> {
+    type Item = (&'a str, &'a Struct);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let path = self.next.take()?;
+        let s = self.jmap.objects.get(path)?.get_struct()?;
+        self.next = s
+            .super_struct
+            .as_deref()
+            .and_then(|p| self.jmap.objects.get_key_value(p))
+            .map(|(p, _)| p.as_str());
+        Some((path, s))
+    }
+}
+
+pub struct Subclasses<'i, 'a> {
+    index: &'i JmapIndex<'a>,
+    stack: Vec<&'a str>,
+    /// bounded in case of cyclic super structs
+    remaining: usize,
+}
+impl<'a> Iterator for Subclasses<'_, 'a> {
+    type Item = &'a str;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let path = self.stack.pop()?;
+        self.stack
+            .extend(self.index.direct_subclasses(path).iter().rev().copied());
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{class, prop, r#struct};
+    use crate::{Address, EFunctionFlags, Object, PropertyType};
+
+    fn int(name: &str, offset: usize) -> Property {
+        prop(name, offset, 4, PropertyType::Int)
+    }
+
+    /// Object <- Actor <- (Pawn <- Character, Info), Actor declares a function and Pawn has
+    /// its super missing
+    fn jmap() -> Jmap {
+        let mut actor = ObjectType::Class(class(
+            Some("/Script/CoreUObject.Object"),
+            vec![int("Tags", 0x28)],
+        ));
+        actor
+            .get_object_mut()
+            .children
+            .insert("/Script/Engine.Actor:K2_DestroyActor".into());
+        let pawn = ObjectType::Class(Class {
+            class_default_object: Some("/Script/Engine.Default__Pawn".into()),
+            ..class(Some("/Script/Engine.Actor"), vec![int("Controller", 0x30)])
+        });
+        let actor_cdo = ObjectType::Object(Object {
+            object_flags: EObjectFlags::RF_ClassDefaultObject,
+            ..Object::new("/Script/Engine.Actor", Some("/Script/Engine".into()))
+        });
+        let objects = [
+            (
+                "/Script/CoreUObject.Object",
+                ObjectType::Class(class(None, vec![int("Flags", 0x8)])),
+            ),
+            ("/Script/Engine.Actor", actor),
+            ("/Script/Engine.Pawn", pawn),
+            (
+                "/Script/Engine.Character",
+                ObjectType::Class(class(Some("/Script/Engine.Pawn"), vec![])),
+            ),
+            (
+                "/Script/Engine.Info",
+                ObjectType::Class(class(Some("/Script/Engine.Actor"), vec![])),
+            ),
+            (
+                "/Script/Engine.Orphan",
+                ObjectType::Class(class(Some("/Script/Missing.Base"), vec![])),
+            ),
+            (
+                "/Script/Engine.Actor:K2_DestroyActor",
+                ObjectType::Function(Function {
+                    r#struct: r#struct("/Script/CoreUObject.Function", None, vec![]),
+                    function_flags: EFunctionFlags::FUNC_Native,
+                    func: Address(0x1000),
+                }),
+            ),
+            ("/Script/Engine.Default__Actor", actor_cdo),
+            (
+                "/Script/Engine.Default__Pawn",
+                ObjectType::Object(Object::new("/Script/Engine.Pawn", None)),
+            ),
+            (
+                "/Game/Maps/Map.Map:PersistentLevel.Pawn",
+                ObjectType::Object(Object::new("/Script/Engine.Pawn", None)),
+            ),
+        ];
+        crate::test_util::jmap(objects)
+    }
+
+    #[test]
+    fn test_object_path_parse() {
+        let cases = [
+            ("/Script/Engine", "/Script/Engine", None, None, "Engine"),
+            (
+                "/Script/Engine.Actor",
+                "/Script/Engine",
+                Some("Actor"),
+                None,
+                "Actor",
+            ),
+            (
+                "/Game/Maps/Map.Map:PersistentLevel.Actor",
+                "/Game/Maps/Map",
+                Some("Map"),
+                Some("PersistentLevel.Actor"),
+                "Actor",
+            ),
+        ];
+        for (path, package, object, subobject, name) in cases {
+            let parsed = ObjectPath::parse(path);
+            assert_eq!(
+                parsed,
+                ObjectPath {
+                    package,
+                    object,
+                    subobject
+                }
+            );
+            assert_eq!(parsed.name(), name);
+            assert_eq!(parsed.is_package(), object.is_none());
+            assert_eq!(parsed.to_string(), path);
+        }
+    }
+
+    #[test]
+    fn test_super_chain() {
+        let jmap = jmap();
+        let index = jmap.index();
+        let chain = |path| index.super_chain(path).map(|(p, _)| p).collect::<Vec<_>>();
+        assert_eq!(
+            chain("/Script/Engine.Character"),
+            [
+                "/Script/Engine.Character",
+                "/Script/Engine.Pawn",
+                "/Script/Engine.Actor",
+                "/Script/CoreUObject.Object",
+            ]
+        );
+        assert_eq!(chain("/Script/Engine.Orphan"), ["/Script/Engine.Orphan"]);
+        assert_eq!(chain("/Script/Missing.Base"), Vec::<&str>::new());
+
+        assert!(index.is_a("/Script/Engine.Character", "/Script/Engine.Actor"));
+        assert!(index.is_a("/Script/Engine.Actor", "/Script/Engine.Actor"));
+        assert!(!index.is_a("/Script/Engine.Info", "/Script/Engine.Pawn"));
+    }
+
+    #[test]
+    fn test_super_chain_cycle() {
+        let mut jmap = jmap();
+        let Some(ObjectType::Class(object)) = jmap.objects.get_mut("/Script/CoreUObject.Object")
+        else {
+            unreachable!()
+        };
+        object.r#struct.super_struct = Some("/Script/Engine.Character".into());
+        let index = jmap.index();
+        assert_eq!(
+            index.super_chain("/Script/Engine.Character").count(),
+            jmap.objects.len()
+        );
+        assert!(index.subclasses("/Script/Engine.Actor").count() <= jmap.objects.len());
+    }
+
+    #[test]
+    fn test_subclasses() {
+        let jmap = jmap();
+        let index = jmap.index();
+        assert_eq!(
+            index.direct_subclasses("/Script/Engine.Actor"),
+            ["/Script/Engine.Info", "/Script/Engine.Pawn"]
+        );
+        assert_eq!(
+            index
+                .subclasses("/Script/CoreUObject.Object")
+                .collect::<Vec<_>>(),
+            [
+                "/Script/Engine.Actor",
+                "/Script/Engine.Info",
+                "/Script/Engine.Pawn",
+                "/Script/Engine.Character",
+            ]
+        );
+        assert_eq!(index.subclasses("/Script/Engine.Character").count(), 0);
+    }
+
+    #[test]
+    fn test_inherited_properties() {
+        let jmap = jmap();
+        let index = jmap.index();
+        let properties: Vec<_> = index
+            .inherited_properties("/Script/Engine.Character")
+            .map(|p| (p.owner, p.property.name.as_str(), p.offset))
+            .collect();
+        assert_eq!(
+            properties,
+            [
+                ("/Script/CoreUObject.Object", "Flags", 0x8),
+                ("/Script/Engine.Actor", "Tags", 0x28),
+                ("/Script/Engine.Pawn", "Controller", 0x30),
+            ]
+        );
+        let tags = index
+            .inherited_properties("/Script/Engine.Pawn")
+            .find(|p| p.property.name == "Tags")
+            .unwrap();
+        assert_eq!(tags.element_offset(2), 0x30);
+    }
+
+    #[test]
+    fn test_lookups() {
+        let jmap = jmap();
+        let index = jmap.index();
+        assert_eq!(
+            index.find_by_name("Pawn"),
+            [
+                "/Game/Maps/Map.Map:PersistentLevel.Pawn",
+                "/Script/Engine.Pawn"
+            ]
+        );
+        assert_eq!(index.find_by_name("Missing"), [] as [&str; 0]);
+
+        let functions: Vec<_> = index
+            .functions("/Script/Engine.Actor")
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(functions, ["/Script/Engine.Actor:K2_DestroyActor"]);
+        assert_eq!(index.functions("/Script/Engine.Pawn").count(), 0);
+
+        assert_eq!(
+            index.instances("/Script/Engine.Pawn"),
+            [
+                "/Game/Maps/Map.Map:PersistentLevel.Pawn",
+                "/Script/Engine.Default__Pawn"
+            ]
+        );
+        // recorded by the class or found by RF_ClassDefaultObject
+        assert_eq!(
+            index.cdo("/Script/Engine.Pawn"),
+            Some("/Script/Engine.Default__Pawn")
+        );
+        assert_eq!(
+            index.cdo("/Script/Engine.Actor"),
+            Some("/Script/Engine.Default__Actor")
+        );
+        assert_eq!(index.cdo("/Script/Engine.Info"), None);
+    }
+}
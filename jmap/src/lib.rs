@@ -3,8 +3,19 @@ use std::collections::{BTreeMap, BTreeSet};
 use bytemuck::{Pod, Zeroable};
 use ordered_float::OrderedFloat;
 use ordermap::OrderMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+pub mod binary;
+pub mod diff;
+pub mod index;
+pub mod migrate;
+pub mod resolve;
+pub mod schema;
+#[cfg(test)]
+mod test_util;
+pub mod validate;
+
 mod base64_serde {
     use base64::prelude::*;
     use serde::{Deserialize, Deserializer, Serializer};
@@ -414,19 +425,76 @@ bitflags::bitflags! {
         const Flags = 0x00000001;
         const NewerVersionExists = 0x00000002;
     }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Pod, Zeroable)]
+    #[repr(C)]
+    pub struct ETextFlag: u32 {
+        const _ = !0;
+
+        const Transient = 0x00000001;
+        const CultureInvariant = 0x00000002;
+        const ConvertedProperty = 0x00000004;
+        const Immutable = 0x00000008;
+        const InitializedFromString = 0x00000010;
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Current version of the jmap JSON format, see [`migrate`] for reading older versions
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Jmap {
+    /// 0 for files written before versioning. Deserializing a [`Jmap`] directly does not
+    /// upgrade older versions, read through [`migrate::from_slice`] and friends for that.
+    #[serde(default)]
+    pub format_version: u32,
     pub metadata: Option<Metadata>,
     pub image_base_address: Address,
+    /// Addresses, offsets and sizes are unknown and written as 0, e.g. for a jmap converted
+    /// from usmap. Layout checks are skipped for such files.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub layout_unknown: bool,
     pub objects: BTreeMap<String, ObjectType>,
     pub vtables: BTreeMap<Address, Vec<Address>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub names: Option<BTreeMap<u32, String>>,
+    /// Errors encountered and skipped over while dumping
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Default for Jmap {
+    /// Empty jmap of the current format version
+    fn default() -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            metadata: None,
+            image_base_address: Address(0),
+            layout_unknown: false,
+            objects: Default::default(),
+            vtables: Default::default(),
+            names: None,
+            diagnostics: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Diagnostic {
+    /// Index in GUObjectArray, if the error is tied to an object
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// Path of the object, if it could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Property which could not be read, the rest of the object was dumped without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property: Option<String>,
+    /// Error followed by its causes, outermost first
+    pub error: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Metadata {
     /// Name or URL of tool used create the reflection data dump
     pub tool: String,
@@ -438,13 +506,13 @@ pub struct Metadata {
     pub build_change_list: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EngineVersion {
     pub major: u16,
     pub minor: u16,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Object {
     pub address: Address,
     pub vtable: Address,
@@ -452,16 +520,31 @@ pub struct Object {
     pub outer: Option<String>,
     pub class: String,
     pub children: BTreeSet<String>,
+    #[schemars(with = "BTreeMap<String, PropertyValue>")]
     pub property_values: ValuesWrapper,
 }
+impl Object {
+    /// Object of `class` with no address, flags, children or property values
+    pub fn new(class: impl Into<String>, outer: Option<String>) -> Self {
+        Self {
+            address: Address(0),
+            vtable: Address(0),
+            object_flags: EObjectFlags::empty(),
+            outer,
+            class: class.into(),
+            children: BTreeSet::new(),
+            property_values: ValuesWrapper::Value(Default::default()),
+        }
+    }
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Package {
     #[serde(flatten)]
     pub object: Object,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Struct {
     #[serde(flatten)]
     pub object: Object,
@@ -470,17 +553,18 @@ pub struct Struct {
     pub properties_size: usize,
     pub min_alignment: usize,
     #[serde(with = "base64_serde")]
+    #[schemars(with = "String")]
     pub script: Vec<u8>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScriptStruct {
     #[serde(flatten)]
     pub r#struct: Struct,
     pub struct_flags: EStructFlags,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Class {
     #[serde(flatten)]
     pub r#struct: Struct,
@@ -490,14 +574,14 @@ pub struct Class {
     /// VTable ptr of any instance of this UClass if found
     pub instance_vtable: Option<Address>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Function {
     #[serde(flatten)]
     pub r#struct: Struct,
     pub function_flags: EFunctionFlags,
     pub func: Address,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Enum {
     #[serde(flatten)]
     pub object: Object,
@@ -506,7 +590,7 @@ pub struct Enum {
     pub cpp_form: ECppForm,
     pub names: Vec<(String, i64)>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize, strum::FromRepr)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, strum::FromRepr)]
 #[repr(u8)]
 pub enum ECppForm {
     Regular,
@@ -514,7 +598,7 @@ pub enum ECppForm {
     EnumClass,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum ObjectType {
     Object(Object),
@@ -535,6 +619,16 @@ impl ObjectType {
             ObjectType::Function(obj) => &obj.r#struct.object,
         }
     }
+    pub fn get_object_mut(&mut self) -> &mut Object {
+        match self {
+            ObjectType::Object(obj) => obj,
+            ObjectType::Package(obj) => &mut obj.object,
+            ObjectType::Enum(obj) => &mut obj.object,
+            ObjectType::ScriptStruct(obj) => &mut obj.r#struct.object,
+            ObjectType::Class(obj) => &mut obj.r#struct.object,
+            ObjectType::Function(obj) => &mut obj.r#struct.object,
+        }
+    }
     pub fn get_struct(&self) -> Option<&Struct> {
         match self {
             ObjectType::Object(_) => None,
@@ -567,7 +661,7 @@ impl ObjectType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Property {
     pub address: Address,
     pub name: String,
@@ -578,7 +672,21 @@ pub struct Property {
     pub r#type: PropertyType,
     pub flags: EPropertyFlags,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Property {
+    /// Single element property with no address, offset, size or flags
+    pub fn new(name: impl Into<String>, r#type: PropertyType) -> Self {
+        Self {
+            address: Address(0),
+            name: name.into(),
+            offset: 0,
+            array_dim: 1,
+            size: 0,
+            r#type,
+            flags: EPropertyFlags::empty(),
+        }
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum PropertyType {
     #[serde(rename = "StructProperty")]
@@ -659,7 +767,10 @@ pub enum PropertyType {
     #[serde(rename = "InterfaceProperty")]
     Interface { interface_class: String },
     #[serde(rename = "FieldPathProperty")]
-    FieldPath,
+    FieldPath {
+        /// Name of the FFieldClass the path points at e.g. "FloatProperty"
+        property_class: Option<String>,
+    },
     #[serde(rename = "OptionalProperty")]
     Optional { inner: Box<Property> },
     #[serde(rename = "FUtf8StrProperty")]
@@ -693,6 +804,36 @@ mod map_as_pairs {
         Ok(pairs.into_iter().collect())
     }
 }
+/// `[]` if unset, `[value]` if set, so a set optional holding null (e.g. an object reference)
+/// stays distinct from an unset one
+mod optional_as_list {
+    use super::PropertyValue;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    pub fn serialize<S>(
+        value: &Option<Box<PropertyValue>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        value.as_slice().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Box<PropertyValue>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut elements: Vec<Box<PropertyValue>> = Vec::deserialize(deserializer)?;
+        if elements.len() > 1 {
+            return Err(D::Error::invalid_length(
+                elements.len(),
+                &"at most one value",
+            ));
+        }
+        Ok(elements.pop())
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(untagged)]
@@ -700,10 +841,11 @@ pub enum PropertyValue {
     Struct(OrderMap<String, PropertyValue>),
     Str(String),
     Name(String),
-    Text, // TODO
-    MulticastInlineDelegate,
-    MulticastSparseDelegate,
-    Delegate,
+    Text(Box<TextPropertyValue>),
+    MulticastInlineDelegate(Vec<DelegatePropertyValue>),
+    MulticastSparseDelegate(Vec<DelegatePropertyValue>),
+    MulticastDelegate(Vec<DelegatePropertyValue>),
+    Delegate(Option<DelegatePropertyValue>),
     Bool(bool),
     Array(Vec<PropertyValue>),
     Enum(EnumPropertyValue),
@@ -721,31 +863,118 @@ pub enum PropertyValue {
     Int(i32),
     Int64(i64),
     Object(Option<String>),
-    WeakObject(String),
-    SoftObject(String),
-    LazyObject(String),
-    Interface(String),
-    FieldPath, // TODO
+    WeakObject(Option<String>),
+    SoftObject(Option<String>),
+    LazyObject(Option<String>),
+    Interface(Option<String>),
+    FieldPath(Option<String>),
+    #[serde(with = "optional_as_list")]
     Optional(Option<Box<PropertyValue>>),
     Utf8Str(String),
     AnsiStr(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Single delegate binding
+#[derive(
+    Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct DelegatePropertyValue {
+    /// Path of the bound object, None if the object is null or no longer alive
+    pub object: Option<String>,
+    pub function_name: String,
+}
+
+#[derive(
+    Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct TextPropertyValue {
+    /// History type backing the text, None if it could not be determined
+    pub history: Option<ETextHistoryType>,
+    pub flags: ETextFlag,
+    /// Localization namespace (Base history only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Localization key (Base history only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Source (native culture) string (Base history only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_string: Option<String>,
+    /// String as currently displayed: localized, generated or culture invariant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_string: Option<String>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    strum::FromRepr,
+)]
+#[repr(i8)]
+pub enum ETextHistoryType {
+    None = -1,
+    Base = 0,
+    NamedFormat,
+    OrderedFormat,
+    ArgumentFormat,
+    AsNumber,
+    AsPercent,
+    AsCurrency,
+    AsDate,
+    AsTime,
+    AsDateTime,
+    Transform,
+    StringTableEntry,
+    TextGenerator,
+}
+impl ETextHistoryType {
+    /// Whether the display string is generated from other data (formatting, number/date conversions, etc.)
+    pub fn is_generated(self) -> bool {
+        matches!(
+            self,
+            Self::NamedFormat
+                | Self::OrderedFormat
+                | Self::ArgumentFormat
+                | Self::AsNumber
+                | Self::AsPercent
+                | Self::AsCurrency
+                | Self::AsDate
+                | Self::AsTime
+                | Self::AsDateTime
+                | Self::Transform
+        )
+    }
+}
+
+#[derive(
+    Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
 #[serde(untagged)]
 pub enum EnumPropertyValue {
     Value(i64),
     Name(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
 #[serde(untagged)]
 pub enum BytePropertyValue {
     Value(u8),
     Name(String),
 }
 
-/// Wrapper for PropertyValues which require external context to properly deserialize
+/// Wrapper for PropertyValues which require external context to properly deserialize. Loaded
+/// values are always `Raw`, see [`Jmap::resolve_values`] to turn them into typed values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ValuesWrapper {
@@ -0,0 +1,240 @@
+//! Upgrades jmap JSON written by older versions of the format to the current one.
+//!
+//! Each migration rewrites the JSON of one format version into the next, so archived dumps
+//! stay readable as the types evolve. Versions:
+//!
+//! 0. No `format_version`. Text, delegate, weak/soft/lazy object, interface, field path and
+//!    optional property values are omitted and `FieldPathProperty` has no `property_class`
+//! 1. Values of all property types, `FieldPathProperty::property_class`
+//!
+//! Fields with a serde default which readers can ignore (e.g. `diagnostics`) are added without
+//! a version bump.
+
+use std::io::Read;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+
+use crate::{FORMAT_VERSION, Jmap};
+
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// `MIGRATIONS[i]` upgrades format version `i` to `i + 1`, `None` if the JSON of version `i`
+/// is already valid for `i + 1`
+const MIGRATIONS: &[Option<Migration>] = &[Some(v0_to_v1)];
+const _: () = assert!(MIGRATIONS.len() == FORMAT_VERSION as usize);
+
+/// Format version of jmap JSON, 0 if it predates versioning
+pub fn format_version(value: &Value) -> Result<u32> {
+    match value.get("format_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_context(|| format!("invalid format_version {version}")),
+    }
+}
+
+/// Upgrade jmap JSON in place to [`FORMAT_VERSION`]
+pub fn migrate(value: &mut Value) -> Result<()> {
+    let version = format_version(value)?;
+    if version > FORMAT_VERSION {
+        bail!(
+            "jmap format version {version} is newer than the latest supported version \
+             {FORMAT_VERSION}, a newer version of jmap is required to read it"
+        );
+    }
+    let Value::Object(root) = value else {
+        bail!("expected jmap to be a JSON object");
+    };
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let Some(migration) = migration else {
+            continue;
+        };
+        migration(root).with_context(|| {
+            format!(
+                "failed to migrate jmap from format version {from} to {}",
+                from + 1
+            )
+        })?;
+    }
+    root.insert("format_version".into(), FORMAT_VERSION.into());
+    Ok(())
+}
+
+/// Add the `property_class` of field path properties, unknown in version 0. Values only gained
+/// new property types, which version 0 omitted, so they are left as they are.
+fn v0_to_v1(root: &mut Map<String, Value>) -> Result<()> {
+    let Some(Value::Object(objects)) = root.get_mut("objects") else {
+        return Ok(());
+    };
+    for obj in objects.values_mut() {
+        if let Some(Value::Array(properties)) = obj.get_mut("properties") {
+            properties.iter_mut().for_each(add_property_class);
+        }
+    }
+    Ok(())
+}
+
+fn add_property_class(prop: &mut Value) {
+    let Value::Object(prop) = prop else {
+        return;
+    };
+    if prop.get("type").and_then(Value::as_str) == Some("FieldPathProperty") {
+        prop.entry("property_class").or_insert(Value::Null);
+    }
+    for inner in ["inner", "key_prop", "value_prop", "container"] {
+        if let Some(inner) = prop.get_mut(inner) {
+            add_property_class(inner);
+        }
+    }
+}
+
+/// Parse jmap JSON of any supported format version
+pub fn from_value(mut value: Value) -> Result<Jmap> {
+    migrate(&mut value)?;
+    Ok(serde_json::from_value(value)?)
+}
+pub fn from_slice(data: &[u8]) -> Result<Jmap> {
+    from_value(serde_json::from_slice(data)?)
+}
+pub fn from_reader(reader: impl Read) -> Result<Jmap> {
+    from_value(serde_json::from_reader(reader)?)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{ObjectType, PropertyType};
+
+    fn object(class: &str, outer: Option<&str>) -> Value {
+        json!({
+            "address": "0x1000",
+            "vtable": "0x2000",
+            "object_flags": "RF_Public",
+            "outer": outer,
+            "class": class,
+            "children": [],
+            "property_values": {}
+        })
+    }
+
+    /// Dump as written before `format_version` and `FieldPath::property_class` existed
+    fn v0() -> Value {
+        let mut class = object("/Script/CoreUObject.Class", Some("/Script/Test"));
+        class.as_object_mut().unwrap().extend(
+            json!({
+                "type": "Class",
+                "super_struct": null,
+                "properties": [{
+                    "address": "0x3000",
+                    "name": "Path",
+                    "offset": 40,
+                    "array_dim": 1,
+                    "size": 32,
+                    "type": "FieldPathProperty",
+                    "flags": "CPF_Edit"
+                }, {
+                    "address": "0x3100",
+                    "name": "Paths",
+                    "offset": 72,
+                    "array_dim": 1,
+                    "size": 16,
+                    "type": "ArrayProperty",
+                    "inner": {
+                        "address": "0x3200",
+                        "name": "Paths",
+                        "offset": 0,
+                        "array_dim": 1,
+                        "size": 32,
+                        "type": "FieldPathProperty",
+                        "flags": ""
+                    },
+                    "flags": "CPF_Edit"
+                }],
+                "properties_size": 88,
+                "min_alignment": 8,
+                "script": "",
+                "class_flags": "CLASS_Native",
+                "class_cast_flags": "",
+                "class_default_object": null
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        let mut r#enum = object("/Script/CoreUObject.Enum", Some("/Script/Test"));
+        r#enum.as_object_mut().unwrap().extend(
+            json!({
+                "type": "Enum",
+                "cpp_type": "EMode",
+                "cpp_form": "EnumClass",
+                "names": [["EMode::A", 0]]
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        json!({
+            "metadata": null,
+            "image_base_address": "0x140000000",
+            "objects": {
+                "/Script/Test.Thing": class,
+                "/Script/Test.EMode": r#enum,
+            },
+            "vtables": {}
+        })
+    }
+
+    #[test]
+    fn test_migrate_v0() -> Result<()> {
+        let value = v0();
+        assert_eq!(format_version(&value)?, 0);
+
+        let mut migrated = value.clone();
+        migrate(&mut migrated)?;
+        let properties = &migrated["objects"]["/Script/Test.Thing"]["properties"];
+        assert_eq!(properties[0].get("property_class"), Some(&Value::Null));
+        assert_eq!(
+            properties[1]["inner"].get("property_class"),
+            Some(&Value::Null)
+        );
+        assert_eq!(migrated["format_version"], FORMAT_VERSION);
+
+        let jmap = from_value(value.clone())?;
+        assert_eq!(jmap.format_version, FORMAT_VERSION);
+        let Some(ObjectType::Class(class)) = jmap.objects.get("/Script/Test.Thing") else {
+            panic!("expected class");
+        };
+        assert_eq!(class.instance_vtable, None);
+        assert!(matches!(
+            class.r#struct.properties[0].r#type,
+            PropertyType::FieldPath {
+                property_class: None
+            }
+        ));
+        let Some(ObjectType::Enum(e)) = jmap.objects.get("/Script/Test.EMode") else {
+            panic!("expected enum");
+        };
+        assert!(e.enum_flags.is_none());
+
+        // reading directly keeps the version it was written with
+        let jmap: Jmap = serde_json::from_value(value)?;
+        assert_eq!(jmap.format_version, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer() {
+        let mut value = v0();
+        value["format_version"] = (FORMAT_VERSION + 1).into();
+        let err = from_value(value).unwrap_err();
+        assert!(err.to_string().contains("newer than the latest supported"));
+
+        let mut value = v0();
+        value["format_version"] = "1".into();
+        assert!(from_value(value).is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! Schema aware deserialization of [`ValuesWrapper::Raw`] property values.
+//!
+//! [`PropertyValue`] is untagged so a round trip through JSON loses the variant: names come
+//! back as strings, integers as whichever variant fits first and static arrays as `Array`.
+//! The owning class (or struct) knows the real type of each property, which is used here to
+//! rebuild the typed values.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result, bail};
+use ordermap::OrderMap;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{Jmap, Property, PropertyType, PropertyValue, ValuesWrapper};
+
+impl Jmap {
+    /// Replace every [`ValuesWrapper::Raw`] with typed values using the property schema of
+    /// the object's class, including inherited properties. Objects whose class, or a struct
+    /// their values need, is missing from the dump keep their raw values. Fails if a value
+    /// does not match its property.
+    pub fn resolve_values(&mut self) -> Result<()> {
+        let mut resolved = vec![];
+        for (path, obj) in &self.objects {
+            let obj = obj.get_object();
+            if let ValuesWrapper::Raw(raw) = &obj.property_values {
+                let values = Resolver { jmap: self }.r#struct(&obj.class, raw);
+                match values {
+                    Ok(values) => resolved.push((path.clone(), values)),
+                    Err(e) if e.downcast_ref::<MissingStruct>().is_some() => {}
+                    Err(e) => return Err(e.context(format!("failed to resolve values of {path}"))),
+                }
+            }
+        }
+        for (path, values) in resolved {
+            self.objects
+                .get_mut(&path)
+                .unwrap()
+                .get_object_mut()
+                .property_values = ValuesWrapper::Value(values);
+        }
+        Ok(())
+    }
+
+    /// Typed values of a single object, resolving them if still raw
+    pub fn resolved_values(&self, path: &str) -> Result<OrderMap<String, PropertyValue>> {
+        let obj = self
+            .objects
+            .get(path)
+            .with_context(|| format!("object {path} not found"))?
+            .get_object();
+        match &obj.property_values {
+            ValuesWrapper::Value(values) => Ok(values.clone()),
+            ValuesWrapper::Raw(raw) => Resolver { jmap: self }.r#struct(&obj.class, raw),
+        }
+    }
+}
+
+/// A class or struct needed to type values is not in the dump
+#[derive(Debug)]
+struct MissingStruct(String);
+impl std::fmt::Display for MissingStruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "struct {} not found", self.0)
+    }
+}
+impl std::error::Error for MissingStruct {}
+
+struct Resolver<'a> {
+    jmap: &'a Jmap,
+}
+impl Resolver<'_> {
+    /// Properties of `struct_path` followed by those of its super structs, the order in which
+    /// the dumper writes values
+    fn properties(&self, struct_path: &str) -> Result<Vec<&Property>> {
+        let mut properties = vec![];
+        let mut current = Some(struct_path);
+        // bounded in case of cyclic super structs
+        for _ in 0..=self.jmap.objects.len() {
+            let Some(path) = current else { break };
+            let s = self
+                .jmap
+                .objects
+                .get(path)
+                .and_then(|obj| obj.get_struct())
+                .ok_or_else(|| MissingStruct(path.to_string()))?;
+            properties.extend(&s.properties);
+            current = s.super_struct.as_deref();
+        }
+        Ok(properties)
+    }
+
+    /// Values are typed in property order rather than the (sorted) order of the raw fields
+    fn r#struct(&self, struct_path: &str, raw: &Value) -> Result<OrderMap<String, PropertyValue>> {
+        let Value::Object(fields) = raw else {
+            bail!("expected object, found {raw}");
+        };
+        let mut values = OrderMap::new();
+        for prop in self.properties(struct_path)? {
+            let Some(value) = fields.get(&prop.name) else {
+                continue;
+            };
+            if values.contains_key(&prop.name) {
+                // shadowed by a property of a derived struct
+                continue;
+            }
+            let value = if prop.array_dim == 1 {
+                self.value(&prop.r#type, value)
+            } else {
+                // static arrays are stored as a list of elements
+                self.array(&prop.r#type, value)
+            }
+            .with_context(|| format!("property {}", prop.name))?;
+            values.insert(prop.name.clone(), value);
+        }
+        if let Some(name) = fields.keys().find(|name| !values.contains_key(*name)) {
+            bail!("{struct_path} has no property {name}");
+        }
+        Ok(values)
+    }
+
+    fn array(&self, element: &PropertyType, raw: &Value) -> Result<PropertyValue> {
+        let Value::Array(elements) = raw else {
+            bail!("expected array, found {raw}");
+        };
+        Ok(PropertyValue::Array(
+            elements
+                .iter()
+                .map(|e| self.value(element, e))
+                .collect::<Result<_>>()?,
+        ))
+    }
+
+    fn value(&self, r#type: &PropertyType, raw: &Value) -> Result<PropertyValue> {
+        use PropertyType as PT;
+        use PropertyValue as PV;
+
+        Ok(match r#type {
+            PT::Struct { r#struct } => PV::Struct(self.r#struct(r#struct, raw)?),
+            PT::Str => PV::Str(de(raw)?),
+            PT::Name => PV::Name(de(raw)?),
+            PT::Text => PV::Text(de(raw)?),
+            PT::MulticastInlineDelegate { .. } => PV::MulticastInlineDelegate(de(raw)?),
+            PT::MulticastSparseDelegate { .. } => PV::MulticastSparseDelegate(de(raw)?),
+            PT::MulticastDelegate { .. } => PV::MulticastDelegate(de(raw)?),
+            PT::Delegate { .. } => PV::Delegate(de(raw)?),
+            PT::Bool { .. } => PV::Bool(de(raw)?),
+            PT::Array { inner } => self.array(&inner.r#type, raw)?,
+            PT::Enum { .. } => PV::Enum(de(raw)?),
+            PT::Map {
+                key_prop,
+                value_prop,
+            } => {
+                let pairs: Vec<(Value, Value)> = de(raw)?;
+                let mut map = BTreeMap::new();
+                for (key, value) in &pairs {
+                    map.insert(
+                        self.value(&key_prop.r#type, key)?,
+                        self.value(&value_prop.r#type, value)?,
+                    );
+                }
+                PV::Map(map)
+            }
+            PT::Set { key_prop } => {
+                let Value::Array(elements) = raw else {
+                    bail!("expected array, found {raw}");
+                };
+                PV::Set(
+                    elements
+                        .iter()
+                        .map(|e| self.value(&key_prop.r#type, e))
+                        .collect::<Result<BTreeSet<_>>>()?,
+                )
+            }
+            PT::Float => PV::Float(de(raw)?),
+            PT::Double => PV::Double(de(raw)?),
+            PT::Byte { .. } => PV::Byte(de(raw)?),
+            PT::UInt16 => PV::UInt16(de(raw)?),
+            PT::UInt32 => PV::UInt32(de(raw)?),
+            PT::UInt64 => PV::UInt64(de(raw)?),
+            PT::Int8 => PV::Int8(de(raw)?),
+            PT::Int16 => PV::Int16(de(raw)?),
+            PT::Int => PV::Int(de(raw)?),
+            PT::Int64 => PV::Int64(de(raw)?),
+            // class properties are object properties holding a UClass
+            PT::Object { .. } | PT::Class { .. } => PV::Object(de(raw)?),
+            PT::WeakObject { .. } => PV::WeakObject(de(raw)?),
+            PT::SoftObject { .. } | PT::SoftClass { .. } => PV::SoftObject(de(raw)?),
+            PT::LazyObject { .. } => PV::LazyObject(de(raw)?),
+            PT::Interface { .. } => PV::Interface(de(raw)?),
+            PT::FieldPath { .. } => PV::FieldPath(de(raw)?),
+            // `[]` if unset, `[value]` if set
+            PT::Optional { inner } => PV::Optional(match de::<Vec<Value>>(raw)?.as_slice() {
+                [] => None,
+                [value] => Some(self.value(&inner.r#type, value)?.into()),
+                _ => bail!("expected at most one optional value, found {raw}"),
+            }),
+            PT::Utf8Str => PV::Utf8Str(de(raw)?),
+            PT::AnsiStr => PV::AnsiStr(de(raw)?),
+        })
+    }
+}
+
+fn de<T: DeserializeOwned>(raw: &Value) -> Result<T> {
+    Ok(T::deserialize(raw)?)
+}
+
+#[cfg(test)]
+mod test {
+    use ordered_float::OrderedFloat;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_util::{class, r#struct};
+    use crate::{EStructFlags, EnumPropertyValue, Object, ObjectType, ScriptStruct};
+
+    fn boxed(name: &str, r#type: PropertyType) -> Box<Property> {
+        Property::new(name, r#type).into()
+    }
+
+    fn instance(class: &str, values: Value) -> ObjectType {
+        ObjectType::Object(Object {
+            property_values: ValuesWrapper::Raw(values),
+            ..Object::new(class, Some("/Script/Test".into()))
+        })
+    }
+
+    fn jmap<const N: usize>(objects: [(&str, ObjectType); N]) -> Jmap {
+        let vector = ScriptStruct {
+            r#struct: r#struct(
+                "/Script/CoreUObject.ScriptStruct",
+                None,
+                vec![
+                    Property::new("X", PropertyType::Float),
+                    Property::new("Y", PropertyType::Float),
+                ],
+            ),
+            struct_flags: EStructFlags::empty(),
+        };
+        let base = ObjectType::Class(class(None, vec![Property::new("Count", PropertyType::Int)]));
+        let thing = ObjectType::Class(class(
+            Some("/Script/Test.Base"),
+            vec![
+                Property::new(
+                    "Location",
+                    PropertyType::Struct {
+                        r#struct: "/Script/Test.Vector".into(),
+                    },
+                ),
+                Property::new(
+                    "Weights",
+                    PropertyType::Map {
+                        key_prop: boxed("Weights_Key", PropertyType::Name),
+                        value_prop: boxed("Weights", PropertyType::Float),
+                    },
+                ),
+                Property::new(
+                    "Mode",
+                    PropertyType::Enum {
+                        container: boxed("UnderlyingType", PropertyType::Byte { r#enum: None }),
+                        r#enum: Some("/Script/Test.EMode".into()),
+                    },
+                ),
+                Property::new(
+                    "Owner",
+                    PropertyType::Optional {
+                        inner: boxed(
+                            "Owner",
+                            PropertyType::Object {
+                                property_class: "/Script/Test.Thing".into(),
+                            },
+                        ),
+                    },
+                ),
+                Property::new(
+                    "Limit",
+                    PropertyType::Optional {
+                        inner: boxed("Limit", PropertyType::Int),
+                    },
+                ),
+                Property {
+                    array_dim: 2,
+                    ..Property::new("Pair", PropertyType::Int)
+                },
+            ],
+        ));
+        crate::test_util::jmap(
+            [
+                ("/Script/Test.Vector", ObjectType::ScriptStruct(vector)),
+                ("/Script/Test.Base", base),
+                ("/Script/Test.Thing", thing),
+            ]
+            .into_iter()
+            .chain(objects),
+        )
+    }
+
+    #[test]
+    fn test_resolve_values() -> Result<()> {
+        let mut jmap = jmap([(
+            "/Script/Test.Default__Thing",
+            instance(
+                "/Script/Test.Thing",
+                json!({
+                    "Count": 3,
+                    "Limit": [],
+                    "Location": { "X": 1.5, "Y": -2.0 },
+                    "Mode": "EMode::B",
+                    "Owner": [null],
+                    "Pair": [1, 2],
+                    "Weights": [["a", 0.5], ["b", 1.0]],
+                }),
+            ),
+        )]);
+        jmap.resolve_values()?;
+
+        use PropertyValue as PV;
+        let float = |f: f32| PV::Float(OrderedFloat(f));
+        // declaration order, own properties before inherited ones like a fresh dump
+        let expected = OrderMap::from_iter([
+            (
+                "Location".to_string(),
+                PV::Struct(OrderMap::from_iter([
+                    ("X".to_string(), float(1.5)),
+                    ("Y".to_string(), float(-2.0)),
+                ])),
+            ),
+            (
+                "Weights".into(),
+                PV::Map(BTreeMap::from_iter([
+                    (PV::Name("a".into()), float(0.5)),
+                    (PV::Name("b".into()), float(1.0)),
+                ])),
+            ),
+            (
+                "Mode".into(),
+                PV::Enum(EnumPropertyValue::Name("EMode::B".into())),
+            ),
+            ("Owner".into(), PV::Optional(Some(PV::Object(None).into()))),
+            ("Limit".into(), PV::Optional(None)),
+            ("Pair".into(), PV::Array(vec![PV::Int(1), PV::Int(2)])),
+            ("Count".into(), PV::Int(3)),
+        ]);
+        let values = jmap.objects["/Script/Test.Default__Thing"]
+            .get_object()
+            .property_values
+            .values()
+            .cloned();
+        assert_eq!(values, Some(expected.clone()));
+
+        // typed values survive a round trip through JSON
+        let raw = serde_json::to_value(&expected)?;
+        assert_eq!(raw["Owner"], json!([null]));
+        assert_eq!(raw["Limit"], json!([]));
+        let mut jmap = self::jmap([(
+            "/Script/Test.Default__Thing",
+            instance("/Script/Test.Thing", raw),
+        )]);
+        jmap.resolve_values()?;
+        assert_eq!(
+            jmap.resolved_values("/Script/Test.Default__Thing")?,
+            expected
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_missing_class() -> Result<()> {
+        let mut jmap = jmap([
+            (
+                "/Script/Test.Default__Other",
+                instance("/Script/Test.Other", json!({ "Count": 1 })),
+            ),
+            (
+                "/Script/Test.Default__Thing",
+                instance("/Script/Test.Thing", json!({ "Count": 1 })),
+            ),
+        ]);
+        jmap.resolve_values()?;
+        let values = |path: &str| &jmap.objects[path].get_object().property_values;
+        assert!(matches!(
+            values("/Script/Test.Default__Other"),
+            ValuesWrapper::Raw(_)
+        ));
+        assert!(values("/Script/Test.Default__Thing").values().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_mismatch() {
+        let mut jmap = jmap([(
+            "/Script/Test.Default__Thing",
+            instance("/Script/Test.Thing", json!({ "Limit": 1 })),
+        )]);
+        let err = jmap.resolve_values().unwrap_err();
+        assert!(format!("{err:#}").contains("Default__Thing"));
+
+        let mut jmap = self::jmap([(
+            "/Script/Test.Default__Thing",
+            instance("/Script/Test.Thing", json!({ "Count": 1, "Unknown": 2 })),
+        )]);
+        let err = jmap.resolve_values().unwrap_err();
+        assert!(format!("{err:#}").contains("has no property Unknown"));
+    }
+}
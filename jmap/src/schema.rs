@@ -0,0 +1,367 @@
+//! JSON Schema of the .jmap format for consumers not written in Rust.
+//!
+//! The schema is derived from the types in this crate so it cannot drift from what is actually
+//! written. Property values are the exception: their exact JSON shape depends on the owning
+//! struct's property types (see [`crate::resolve`]), so the schema only describes the union of
+//! all value shapes.
+
+use std::borrow::Cow;
+
+use anyhow::{Result, anyhow};
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde_json::Value;
+
+use crate::{
+    Address, DelegatePropertyValue, EClassCastFlags, EClassFlags, EEnumFlags, EFunctionFlags,
+    EObjectFlags, EPropertyFlags, EStructFlags, ETextFlag, Jmap, PropertyValue, TextPropertyValue,
+};
+
+/// JSON Schema (draft 2020-12) of a .jmap file
+pub fn schema() -> Schema {
+    schemars::schema_for!(Jmap)
+}
+
+/// Validate jmap JSON against [`schema`]. Returns one message per violation, prefixed with
+/// the JSON pointer of the offending value.
+pub fn validate(value: &Value) -> Result<Vec<String>> {
+    let schema = schema().to_value();
+    let validator = jsonschema::validator_for(&schema).map_err(|e| anyhow!("{e}"))?;
+    Ok(validator
+        .iter_errors(value)
+        .map(|e| format!("{}: {e}", e.instance_path()))
+        .collect())
+}
+
+impl JsonSchema for Address {
+    fn schema_name() -> Cow<'static, str> {
+        "Address".into()
+    }
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Written as a hex string e.g. \"0x7ff6db379e00\", decimal numbers and decimal strings are also accepted",
+            "anyOf": [
+                { "type": "string", "pattern": "^(0x[0-9a-fA-F]+|[0-9]+)$" },
+                { "type": "integer", "minimum": 0 }
+            ]
+        })
+    }
+}
+
+impl JsonSchema for PropertyValue {
+    fn schema_name() -> Cow<'static, str> {
+        "PropertyValue".into()
+    }
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let value = generator.subschema_for::<PropertyValue>();
+        let text = generator.subschema_for::<TextPropertyValue>();
+        let delegate = generator.subschema_for::<DelegatePropertyValue>();
+        json_schema!({
+            "description": "The shape of a value depends on the type of the property it belongs to, found in the properties of the object's class and its super structs",
+            "anyOf": [
+                {
+                    "description": "Unset object reference, field path or delegate",
+                    "type": "null"
+                },
+                { "description": "BoolProperty", "type": "boolean" },
+                {
+                    "description": "Numeric properties, byte and enum properties as their value",
+                    "type": "number"
+                },
+                {
+                    "description": "Strings and names, object paths, field paths, byte and enum properties as their name",
+                    "type": "string"
+                },
+                {
+                    "description": "Array and Set elements, static array elements, Map as [key, value] pairs, Optional as [] if unset or [value] if set, multicast delegate bindings",
+                    "type": "array",
+                    "items": value
+                },
+                text,
+                delegate,
+                {
+                    "description": "StructProperty values by property name",
+                    "type": "object",
+                    "additionalProperties": value,
+                    "not": {
+                        "anyOf": [
+                            { "required": ["history", "flags"] },
+                            { "required": ["object", "function_name"] }
+                        ]
+                    }
+                }
+            ]
+        })
+    }
+}
+
+/// bitflags serialize as flag names joined by " | " with any unnamed bits as a hex number
+macro_rules! flags_schema {
+    ($($flags:ident),* $(,)?) => {$(
+        impl JsonSchema for $flags {
+            fn schema_name() -> Cow<'static, str> {
+                stringify!($flags).into()
+            }
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                let names = <$flags as bitflags::Flags>::FLAGS
+                    .iter()
+                    .filter(|f| f.is_named())
+                    .map(|f| f.name())
+                    .collect::<Vec<_>>()
+                    .join("|");
+                let flag = format!("({names}|0x[0-9a-fA-F]+)");
+                json_schema!({
+                    "description": "Flag names separated by \" | \", unnamed bits as a hex number",
+                    "type": "string",
+                    "pattern": format!("^({flag}( \\| {flag})*)?$")
+                })
+            }
+        }
+    )*};
+}
+flags_schema!(
+    EObjectFlags,
+    EFunctionFlags,
+    EClassFlags,
+    EClassCastFlags,
+    EPropertyFlags,
+    EStructFlags,
+    EEnumFlags,
+    ETextFlag,
+);
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use ordermap::OrderMap;
+
+    use super::*;
+    use crate::*;
+
+    fn object(outer: Option<&str>, class: &str, values: OrderMap<String, PropertyValue>) -> Object {
+        Object {
+            address: Address(0x1000),
+            vtable: Address(0x2000),
+            object_flags: EObjectFlags::RF_Public | EObjectFlags::RF_Transient,
+            property_values: values.into(),
+            ..Object::new(class, outer.map(str::to_string))
+        }
+    }
+
+    fn property(name: &str, offset: usize, size: usize, r#type: PropertyType) -> Property {
+        Property {
+            address: Address(0x3000),
+            offset,
+            size,
+            flags: EPropertyFlags::CPF_Edit | EPropertyFlags::from_bits_retain(1 << 63),
+            ..Property::new(name, r#type)
+        }
+    }
+
+    fn sample() -> Jmap {
+        let mut objects = BTreeMap::new();
+        let properties = vec![
+            property(
+                "Location",
+                0x28,
+                0xc,
+                PropertyType::Struct {
+                    r#struct: "/Script/Test.Vector".into(),
+                },
+            ),
+            property("Tag", 0x34, 0x8, PropertyType::Name),
+            property(
+                "Ids",
+                0x40,
+                0x10,
+                PropertyType::Array {
+                    inner: property("Ids", 0, 4, PropertyType::Int).into(),
+                },
+            ),
+            property(
+                "Weights",
+                0x50,
+                0x50,
+                PropertyType::Map {
+                    key_prop: property("Weights_Key", 0, 8, PropertyType::Name).into(),
+                    value_prop: property("Weights", 0, 4, PropertyType::Float).into(),
+                },
+            ),
+            property(
+                "Mode",
+                0xa0,
+                1,
+                PropertyType::Enum {
+                    container: property(
+                        "UnderlyingType",
+                        0,
+                        1,
+                        PropertyType::Byte { r#enum: None },
+                    )
+                    .into(),
+                    r#enum: Some("/Script/Test.EMode".into()),
+                },
+            ),
+            property(
+                "bEnabled",
+                0xa1,
+                1,
+                PropertyType::Bool {
+                    field_size: 1,
+                    byte_offset: 0,
+                    byte_mask: 1,
+                    field_mask: 1,
+                },
+            ),
+            property(
+                "Owner",
+                0xa8,
+                8,
+                PropertyType::Object {
+                    property_class: "/Script/Test.Thing".into(),
+                },
+            ),
+        ];
+        objects.insert(
+            "/Script/Test.Thing".into(),
+            ObjectType::Class(Class {
+                r#struct: Struct {
+                    object: object(
+                        Some("/Script/Test"),
+                        "/Script/CoreUObject.Class",
+                        Default::default(),
+                    ),
+                    super_struct: Some("/Script/CoreUObject.Object".into()),
+                    properties,
+                    properties_size: 0xb0,
+                    min_alignment: 8,
+                    script: vec![1, 2, 3],
+                },
+                class_flags: EClassFlags::CLASS_Native,
+                class_cast_flags: EClassCastFlags::empty(),
+                class_default_object: Some("/Script/Test.Default__Thing".into()),
+                instance_vtable: Some(Address(0x2000)),
+            }),
+        );
+        objects.insert(
+            "/Script/Test.EMode".into(),
+            ObjectType::Enum(Enum {
+                object: object(
+                    Some("/Script/Test"),
+                    "/Script/CoreUObject.Enum",
+                    Default::default(),
+                ),
+                cpp_type: "EMode".into(),
+                enum_flags: Some(EEnumFlags::empty()),
+                cpp_form: ECppForm::EnumClass,
+                names: vec![("EMode::A".into(), 0), ("EMode::B".into(), 1)],
+            }),
+        );
+
+        let values = OrderMap::from_iter([
+            (
+                "Location".to_string(),
+                PropertyValue::Struct(OrderMap::from_iter([(
+                    "X".to_string(),
+                    PropertyValue::Float(1.5.into()),
+                )])),
+            ),
+            ("Tag".into(), PropertyValue::Name("None".into())),
+            (
+                "Ids".into(),
+                PropertyValue::Array(vec![PropertyValue::Int(1), PropertyValue::Int(-2)]),
+            ),
+            (
+                "Weights".into(),
+                PropertyValue::Map(BTreeMap::from_iter([(
+                    PropertyValue::Name("a".into()),
+                    PropertyValue::Float(0.5.into()),
+                )])),
+            ),
+            (
+                "Mode".into(),
+                PropertyValue::Enum(EnumPropertyValue::Name("EMode::B".into())),
+            ),
+            ("bEnabled".into(), PropertyValue::Bool(true)),
+            ("Owner".into(), PropertyValue::Object(None)),
+            (
+                "OnChanged".into(),
+                PropertyValue::MulticastInlineDelegate(vec![DelegatePropertyValue {
+                    object: Some("/Script/Test.Default__Thing".into()),
+                    function_name: "Changed".into(),
+                }]),
+            ),
+            ("Limit".into(), PropertyValue::Optional(None)),
+            (
+                "Text".into(),
+                PropertyValue::Text(
+                    TextPropertyValue {
+                        history: Some(ETextHistoryType::Base),
+                        flags: ETextFlag::Immutable,
+                        namespace: None,
+                        key: Some("key".into()),
+                        source_string: Some("text".into()),
+                        display_string: Some("text".into()),
+                    }
+                    .into(),
+                ),
+            ),
+        ]);
+        objects.insert(
+            "/Script/Test.Default__Thing".into(),
+            ObjectType::Object(object(Some("/Script/Test"), "/Script/Test.Thing", values)),
+        );
+
+        Jmap {
+            format_version: FORMAT_VERSION,
+            metadata: None,
+            image_base_address: Address(0x140000000),
+            layout_unknown: false,
+            objects,
+            vtables: BTreeMap::from_iter([(Address(0x2000), vec![Address(0x1400)])]),
+            names: Some(BTreeMap::from_iter([(0, "None".into())])),
+            diagnostics: vec![Diagnostic {
+                index: Some(3),
+                path: None,
+                property: None,
+                error: vec!["failed".into()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_schema_matches_types() -> Result<()> {
+        let value = serde_json::to_value(sample())?;
+        assert_eq!(validate(&value)?, Vec::<String>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_rejects_invalid() -> Result<()> {
+        let valid = serde_json::to_value(sample())?;
+
+        let mut value = valid.clone();
+        value["objects"]["/Script/Test.Thing"]["properties"][0]["type"] = "BogusProperty".into();
+        assert!(!validate(&value)?.is_empty());
+
+        let mut value = valid.clone();
+        value["objects"]["/Script/Test.Thing"]["class_flags"] = "CLASS_Bogus".into();
+        assert!(!validate(&value)?.is_empty());
+
+        let mut value = valid.clone();
+        value["format_version"] = "1".into();
+        assert!(!validate(&value)?.is_empty());
+
+        // text with flags which are not a flags string
+        let mut value = valid.clone();
+        let values = &mut value["objects"]["/Script/Test.Default__Thing"]["property_values"];
+        values["Text"]["flags"] = 5.into();
+        assert!(!validate(&value)?.is_empty());
+
+        let mut value = valid;
+        let values = &mut value["objects"]["/Script/Test.Default__Thing"]["property_values"];
+        values["Delegate"] = serde_json::json!({ "object": null, "function_name": 1 });
+        assert!(!validate(&value)?.is_empty());
+        Ok(())
+    }
+}
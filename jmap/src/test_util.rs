@@ -0,0 +1,46 @@
+//! Fixtures shared by the unit tests
+
+use crate::{
+    Class, EClassCastFlags, EClassFlags, Jmap, Object, ObjectType, Property, PropertyType, Struct,
+};
+
+/// Struct whose object is of `class`, 0x40 bytes with 8 byte alignment
+pub fn r#struct(class: &str, super_struct: Option<&str>, properties: Vec<Property>) -> Struct {
+    Struct {
+        object: Object::new(class, None),
+        super_struct: super_struct.map(str::to_string),
+        properties,
+        properties_size: 0x40,
+        min_alignment: 8,
+        script: vec![],
+    }
+}
+
+/// Native class, see [`r#struct`]
+pub fn class(super_struct: Option<&str>, properties: Vec<Property>) -> Class {
+    Class {
+        r#struct: r#struct("/Script/CoreUObject.Class", super_struct, properties),
+        class_flags: EClassFlags::CLASS_Native,
+        class_cast_flags: EClassCastFlags::empty(),
+        class_default_object: None,
+        instance_vtable: None,
+    }
+}
+
+pub fn prop(name: &str, offset: usize, size: usize, r#type: PropertyType) -> Property {
+    Property {
+        offset,
+        size,
+        ..Property::new(name, r#type)
+    }
+}
+
+pub fn jmap<'a>(objects: impl IntoIterator<Item = (&'a str, ObjectType)>) -> Jmap {
+    Jmap {
+        objects: objects
+            .into_iter()
+            .map(|(path, obj)| (path.to_string(), obj))
+            .collect(),
+        ..Default::default()
+    }
+}
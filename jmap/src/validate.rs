@@ -0,0 +1,546 @@
+//! Consistency checks for a loaded [`Jmap`]: dangling or mistyped references and broken
+//! struct layouts, which otherwise only surface as wrong output in generated SDKs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, Jmap, ObjectType, Property, PropertyType, Struct};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    /// Path of the object the issue was found in
+    pub path: String,
+    /// Property, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    #[serde(flatten)]
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum IssueKind {
+    /// `field` refers to an object which does not exist
+    Unresolved { field: String, target: String },
+    /// `field` refers to an object of the wrong type
+    WrongType {
+        field: String,
+        target: String,
+        expected: String,
+        found: String,
+    },
+    /// Property extends past the end of the struct
+    OutOfBounds {
+        offset: usize,
+        size: usize,
+        properties_size: usize,
+    },
+    /// Property shares memory with another (non-bitfield) property of the same struct
+    Overlap { other: String },
+    /// `instance_vtable` has no entry in `vtables`
+    MissingVTable { vtable: Address },
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.member {
+            Some(member) => write!(f, "{}:{member}: ", self.path)?,
+            None => write!(f, "{}: ", self.path)?,
+        }
+        match &self.kind {
+            IssueKind::Unresolved { field, target } => {
+                write!(f, "{field} {target} does not exist")
+            }
+            IssueKind::WrongType {
+                field,
+                target,
+                expected,
+                found,
+            } => write!(f, "{field} {target} is a {found}, expected {expected}"),
+            IssueKind::OutOfBounds {
+                offset,
+                size,
+                properties_size,
+            } => write!(
+                f,
+                "0x{offset:x} + 0x{size:x} exceeds properties size 0x{properties_size:x}"
+            ),
+            IssueKind::Overlap { other } => write!(f, "overlaps {other}"),
+            IssueKind::MissingVTable { vtable } => write!(f, "instance vtable {vtable} missing"),
+        }
+    }
+}
+
+/// Expected type of a referenced object
+#[derive(Clone, Copy)]
+enum Expected {
+    Any,
+    Object,
+    Enum,
+    ScriptStruct,
+    Class,
+    Function,
+}
+impl Expected {
+    fn matches(self, obj: &ObjectType) -> bool {
+        match self {
+            Expected::Any => true,
+            Expected::Object => matches!(obj, ObjectType::Object(_)),
+            Expected::Enum => matches!(obj, ObjectType::Enum(_)),
+            Expected::ScriptStruct => matches!(obj, ObjectType::ScriptStruct(_)),
+            Expected::Class => matches!(obj, ObjectType::Class(_)),
+            Expected::Function => matches!(obj, ObjectType::Function(_)),
+        }
+    }
+    fn name(self) -> &'static str {
+        match self {
+            Expected::Any => "any",
+            Expected::Object => "Object",
+            Expected::Enum => "Enum",
+            Expected::ScriptStruct => "ScriptStruct",
+            Expected::Class => "Class",
+            Expected::Function => "Function",
+        }
+    }
+}
+
+fn type_name(obj: &ObjectType) -> &'static str {
+    match obj {
+        ObjectType::Object(_) => "Object",
+        ObjectType::Package(_) => "Package",
+        ObjectType::Enum(_) => "Enum",
+        ObjectType::ScriptStruct(_) => "ScriptStruct",
+        ObjectType::Class(_) => "Class",
+        ObjectType::Function(_) => "Function",
+    }
+}
+
+impl Jmap {
+    /// Check that all references resolve to objects of the expected type, that struct layouts
+    /// are sane (unless [`Jmap::layout_unknown`]) and that instance vtables exist. Returns an
+    /// empty list for a consistent jmap.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut v = Validator {
+            jmap: self,
+            issues: vec![],
+        };
+        for (path, obj) in &self.objects {
+            v.object(path, obj);
+        }
+        v.issues
+    }
+}
+
+struct Validator<'a> {
+    jmap: &'a Jmap,
+    issues: Vec<Issue>,
+}
+impl Validator<'_> {
+    fn push(&mut self, path: &str, member: Option<&str>, kind: IssueKind) {
+        self.issues.push(Issue {
+            path: path.to_string(),
+            member: member.map(str::to_string),
+            kind,
+        });
+    }
+
+    fn reference(
+        &mut self,
+        path: &str,
+        member: Option<&str>,
+        field: &str,
+        target: &str,
+        expected: Expected,
+    ) {
+        match self.jmap.objects.get(target) {
+            None => self.push(
+                path,
+                member,
+                IssueKind::Unresolved {
+                    field: field.to_string(),
+                    target: target.to_string(),
+                },
+            ),
+            Some(obj) if !expected.matches(obj) => self.push(
+                path,
+                member,
+                IssueKind::WrongType {
+                    field: field.to_string(),
+                    target: target.to_string(),
+                    expected: expected.name().to_string(),
+                    found: type_name(obj).to_string(),
+                },
+            ),
+            Some(_) => {}
+        }
+    }
+
+    fn object(&mut self, path: &str, obj: &ObjectType) {
+        let object = obj.get_object();
+        self.reference(path, None, "class", &object.class, Expected::Class);
+        if let Some(outer) = &object.outer {
+            self.reference(path, None, "outer", outer, Expected::Any);
+        }
+        for child in &object.children {
+            self.reference(path, None, "children", child, Expected::Any);
+        }
+
+        let super_expected = match obj {
+            ObjectType::ScriptStruct(_) => Expected::ScriptStruct,
+            ObjectType::Class(_) => Expected::Class,
+            ObjectType::Function(_) => Expected::Function,
+            _ => Expected::Any,
+        };
+        if let Some(s) = obj.get_struct() {
+            self.r#struct(path, s, super_expected);
+        }
+
+        if let Some(class) = obj.get_class() {
+            if let Some(cdo) = &class.class_default_object {
+                self.reference(path, None, "class_default_object", cdo, Expected::Object);
+            }
+            match class.instance_vtable {
+                Some(vtable) if !self.jmap.vtables.contains_key(&vtable) => {
+                    self.push(path, None, IssueKind::MissingVTable { vtable })
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn r#struct(&mut self, path: &str, s: &Struct, super_expected: Expected) {
+        if let Some(super_struct) = &s.super_struct {
+            self.reference(path, None, "super_struct", super_struct, super_expected);
+        }
+
+        for prop in &s.properties {
+            self.property_type(path, Some(prop.name.as_str()), &prop.r#type);
+        }
+        if self.jmap.layout_unknown {
+            return;
+        }
+
+        let mut ranges = vec![];
+        for prop in &s.properties {
+            let member = Some(prop.name.as_str());
+
+            let size = prop.size * prop.array_dim;
+            if prop.offset + size > s.properties_size {
+                self.push(
+                    path,
+                    member,
+                    IssueKind::OutOfBounds {
+                        offset: prop.offset,
+                        size,
+                        properties_size: s.properties_size,
+                    },
+                );
+            }
+            if size > 0 && !is_bitfield(prop) {
+                ranges.push((prop.offset, prop.offset + size, &prop.name));
+            }
+        }
+
+        ranges.sort();
+        // compare each range with the furthest reaching one before it
+        let mut furthest: Option<(usize, &String)> = None;
+        for (start, end, name) in ranges {
+            if let Some((furthest_end, other)) = furthest {
+                if start < furthest_end {
+                    self.push(
+                        path,
+                        Some(name.as_str()),
+                        IssueKind::Overlap {
+                            other: other.clone(),
+                        },
+                    );
+                }
+                if end <= furthest_end {
+                    continue;
+                }
+            }
+            furthest = Some((end, name));
+        }
+    }
+
+    fn property_type(&mut self, path: &str, member: Option<&str>, r#type: &PropertyType) {
+        use PropertyType as PT;
+        match r#type {
+            PT::Struct { r#struct } => {
+                self.reference(path, member, "struct", r#struct, Expected::ScriptStruct)
+            }
+            PT::MulticastInlineDelegate { signature_function }
+            | PT::MulticastSparseDelegate { signature_function }
+            | PT::MulticastDelegate { signature_function }
+            | PT::Delegate { signature_function } => {
+                if let Some(func) = signature_function {
+                    self.reference(path, member, "signature_function", func, Expected::Function);
+                }
+            }
+            PT::Array { inner } | PT::Optional { inner } => {
+                self.property_type(path, member, &inner.r#type)
+            }
+            PT::Enum { container, r#enum } => {
+                self.property_type(path, member, &container.r#type);
+                if let Some(e) = r#enum {
+                    self.reference(path, member, "enum", e, Expected::Enum);
+                }
+            }
+            PT::Byte { r#enum } => {
+                if let Some(e) = r#enum {
+                    self.reference(path, member, "enum", e, Expected::Enum);
+                }
+            }
+            PT::Map {
+                key_prop,
+                value_prop,
+            } => {
+                self.property_type(path, member, &key_prop.r#type);
+                self.property_type(path, member, &value_prop.r#type);
+            }
+            PT::Set { key_prop } => self.property_type(path, member, &key_prop.r#type),
+            PT::Object { property_class }
+            | PT::WeakObject { property_class }
+            | PT::SoftObject { property_class }
+            | PT::LazyObject { property_class } => self.reference(
+                path,
+                member,
+                "property_class",
+                property_class,
+                Expected::Class,
+            ),
+            PT::Class {
+                property_class,
+                meta_class,
+            }
+            | PT::SoftClass {
+                property_class,
+                meta_class,
+            } => {
+                self.reference(
+                    path,
+                    member,
+                    "property_class",
+                    property_class,
+                    Expected::Class,
+                );
+                self.reference(path, member, "meta_class", meta_class, Expected::Class);
+            }
+            PT::Interface { interface_class } => self.reference(
+                path,
+                member,
+                "interface_class",
+                interface_class,
+                Expected::Class,
+            ),
+            PT::Str
+            | PT::Name
+            | PT::Text
+            | PT::Bool { .. }
+            | PT::Float
+            | PT::Double
+            | PT::UInt16
+            | PT::UInt32
+            | PT::UInt64
+            | PT::Int8
+            | PT::Int16
+            | PT::Int
+            | PT::Int64
+            | PT::FieldPath { .. }
+            | PT::Utf8Str
+            | PT::AnsiStr => {}
+        }
+    }
+}
+
+/// Bitfield bools share their byte with other bitfields
+fn is_bitfield(prop: &Property) -> bool {
+    matches!(prop.r#type, PropertyType::Bool { field_mask, .. } if field_mask != 0xff)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::Class;
+    use crate::test_util::{class, prop};
+
+    fn bitfield(name: &str, offset: usize, field_mask: u8) -> Property {
+        prop(
+            name,
+            offset,
+            1,
+            PropertyType::Bool {
+                field_size: 1,
+                byte_offset: 0,
+                byte_mask: field_mask,
+                field_mask,
+            },
+        )
+    }
+
+    /// `/Script/Test.Thing` deriving from Object next to the classes every object refers to
+    fn jmap(mut thing: Class) -> Jmap {
+        thing.r#struct.super_struct = Some("/Script/CoreUObject.Object".into());
+        crate::test_util::jmap([
+            (
+                "/Script/CoreUObject.Class",
+                ObjectType::Class(class(None, vec![])),
+            ),
+            (
+                "/Script/CoreUObject.Object",
+                ObjectType::Class(class(None, vec![])),
+            ),
+            ("/Script/Test.Thing", ObjectType::Class(thing)),
+        ])
+    }
+
+    fn issue(member: Option<&str>, kind: IssueKind) -> Issue {
+        Issue {
+            path: "/Script/Test.Thing".into(),
+            member: member.map(str::to_string),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_validate_consistent() {
+        let jmap = jmap(class(
+            None,
+            vec![
+                prop(
+                    "Owner",
+                    0x28,
+                    8,
+                    PropertyType::Object {
+                        property_class: "/Script/CoreUObject.Object".into(),
+                    },
+                ),
+                bitfield("bA", 0x30, 1),
+                bitfield("bB", 0x30, 2),
+            ],
+        ));
+        assert_eq!(jmap.validate(), []);
+    }
+
+    #[test]
+    fn test_validate_references() {
+        let mut thing = class(
+            None,
+            vec![
+                prop(
+                    "Owner",
+                    0x28,
+                    8,
+                    PropertyType::Object {
+                        property_class: "/Script/Test.Missing".into(),
+                    },
+                ),
+                prop(
+                    "Location",
+                    0x30,
+                    0xc,
+                    PropertyType::Struct {
+                        r#struct: "/Script/CoreUObject.Object".into(),
+                    },
+                ),
+            ],
+        );
+        thing.class_default_object = Some("/Script/Test.Default__Thing".into());
+        let issues = jmap(thing).validate();
+        assert_eq!(
+            issues,
+            [
+                issue(
+                    Some("Owner"),
+                    IssueKind::Unresolved {
+                        field: "property_class".into(),
+                        target: "/Script/Test.Missing".into(),
+                    }
+                ),
+                issue(
+                    Some("Location"),
+                    IssueKind::WrongType {
+                        field: "struct".into(),
+                        target: "/Script/CoreUObject.Object".into(),
+                        expected: "ScriptStruct".into(),
+                        found: "Class".into(),
+                    }
+                ),
+                issue(
+                    None,
+                    IssueKind::Unresolved {
+                        field: "class_default_object".into(),
+                        target: "/Script/Test.Default__Thing".into(),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            issues[1].to_string(),
+            "/Script/Test.Thing:Location: struct /Script/CoreUObject.Object is a Class, \
+             expected ScriptStruct"
+        );
+    }
+
+    #[test]
+    fn test_validate_layout() {
+        let mut jmap = jmap(class(
+            None,
+            vec![
+                prop("A", 0x28, 4, PropertyType::Int),
+                prop("B", 0x2a, 4, PropertyType::Int),
+                // bitfields share a byte, a full bool does not
+                bitfield("bC", 0x30, 1),
+                bitfield("bD", 0x30, 2),
+                bitfield("bE", 0x30, 0xff),
+                Property {
+                    array_dim: 2,
+                    ..prop("F", 0x34, 4, PropertyType::Int)
+                },
+                prop("G", 0x38, 4, PropertyType::Int),
+                prop("H", 0x3e, 4, PropertyType::Int),
+            ],
+        ));
+        let overlap = |other: &str| IssueKind::Overlap {
+            other: other.into(),
+        };
+        assert_eq!(
+            jmap.validate(),
+            [
+                issue(
+                    Some("H"),
+                    IssueKind::OutOfBounds {
+                        offset: 0x3e,
+                        size: 4,
+                        properties_size: 0x40,
+                    }
+                ),
+                issue(Some("B"), overlap("A")),
+                issue(Some("G"), overlap("F")),
+            ]
+        );
+
+        jmap.layout_unknown = true;
+        assert_eq!(jmap.validate(), []);
+    }
+
+    #[test]
+    fn test_validate_vtable() {
+        let mut thing = class(None, vec![]);
+        thing.instance_vtable = Some(Address(0x2000));
+        let mut jmap = jmap(thing);
+        assert_eq!(
+            jmap.validate(),
+            [issue(
+                None,
+                IssueKind::MissingVTable {
+                    vtable: Address(0x2000)
+                }
+            )]
+        );
+
+        jmap.vtables = BTreeMap::from_iter([(Address(0x2000), vec![Address(0x1400)])]);
+        assert_eq!(jmap.validate(), []);
+    }
+}
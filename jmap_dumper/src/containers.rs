@@ -1,5 +1,5 @@
-use crate::mem::{Ctx, Pod};
-use anyhow::Result;
+use crate::mem::{Ctx, Mem, Pod};
+use anyhow::{Context as _, Result};
 use derive_where::derive_where;
 use std::collections::BTreeMap;
 
@@ -128,64 +128,110 @@ impl Ptr<FName> {
         let offset = self.ctx().struct_member("FName", "ComparisonIndex");
         self.byte_offset(offset).cast()
     }
+    /// Not present with outlined numbers (UE_FNAME_OUTLINE_NUMBER)
     pub fn number(&self) -> Ptr<u32> {
         let offset = self.ctx().struct_member("FName", "Number");
         self.byte_offset(offset).cast()
     }
     pub fn read(&self) -> Result<String> {
-        let number = self.number().read()?;
+        // outlined numbers are resolved through the numbered name entry
+        let number = if self.ctx().outline_number {
+            0
+        } else {
+            self.number().read()?
+        };
         let comparison_index = self.comparison_index().value().read()?;
         resolve_fname(self.ctx(), comparison_index, number)
     }
 }
 
 pub fn resolve_fname(ctx: &Ctx, comparison_index: u32, number: u32) -> Result<String> {
+    let caching = ctx.caching();
+    let cached = caching
+        .then(|| {
+            ctx.fname_cache
+                .read()
+                .unwrap()
+                .get(&comparison_index)
+                .cloned()
+        })
+        .flatten();
+    let base = match cached {
+        Some(base) => base,
+        None => {
+            let base = read_fname_entry(ctx, comparison_index)?;
+            if caching {
+                ctx.fname_cache
+                    .write()
+                    .unwrap()
+                    .insert(comparison_index, base.clone());
+            }
+            base
+        }
+    };
+    Ok(if number == 0 {
+        base
+    } else {
+        format!("{base}_{}", number - 1)
+    })
+}
+
+/// Elements per chunk of the pre-4.22 `TNameEntryArray`
+const LEGACY_NAMES_PER_CHUNK: u32 = 0x4000;
+/// Offset of `NumElements` in `TNameEntryArray`, following the fixed size chunk table
+const LEGACY_NUM_ELEMENTS_OFFSET: u64 = 0x400;
+
+/// Decode an ANSI FName entry. UE stores ANSI names as Latin-1, so every byte is a char.
+fn decode_ansi(data: &[u8]) -> String {
+    data.iter().map(|&b| b as char).collect()
+}
+
+/// Read a pre-4.22 `FNameEntry`: `Index` (low bit set for wide names), `HashNext`, then
+/// the null terminated name
+fn read_legacy_fname_entry(entry: &Ptr<()>) -> Result<String> {
+    let index = entry.cast::<u32>().read()?;
+    let is_wide = (index & 1) == 1;
+    let char_data = entry.byte_offset(0x10);
+
+    let base = if is_wide {
+        let mut data = vec![];
+        let char_data = char_data.cast::<u16>();
+        for i in 0.. {
+            let next = char_data.offset(i).read()?;
+            if next == 0 {
+                break;
+            }
+            data.push(next);
+        }
+        String::from_utf16(&data)?
+    } else {
+        let mut data = vec![];
+        let char_data = char_data.cast::<u8>();
+        for i in 0.. {
+            let next = char_data.offset(i).read()?;
+            if next == 0 {
+                break;
+            }
+            data.push(next);
+        }
+        decode_ansi(&data)
+    };
+    Ok(base)
+}
+
+fn read_fname_entry(ctx: &Ctx, comparison_index: u32) -> Result<String> {
     let fnamepool = ctx.fnamepool;
     let case_preserving = ctx.case_preserving;
 
     if ctx.ue_version() < (4, 22) {
         let chunks = Ptr::<Ptr<Ptr<Ptr<()>>>>::new(fnamepool, ctx.clone())?.read()?;
 
-        let per_chunk = 0x4000;
-
-        let chunk = comparison_index / per_chunk;
-        let offset = comparison_index % per_chunk;
+        let chunk = comparison_index / LEGACY_NAMES_PER_CHUNK;
+        let offset = comparison_index % LEGACY_NAMES_PER_CHUNK;
 
         let chunk = chunks.offset(chunk as usize).read()?;
         let entry = chunk.offset(offset as usize).read()?;
-
-        let index = entry.cast::<u32>().read()?;
-        let is_wide = (index & 1) == 1;
-        let char_data = entry.byte_offset(0x10);
-
-        let base = if is_wide {
-            let mut data = vec![];
-            let char_data = char_data.cast::<u16>();
-            for i in 0.. {
-                let next = char_data.offset(i).read()?;
-                if next == 0 {
-                    break;
-                }
-                data.push(next);
-            }
-            String::from_utf16(&data)?
-        } else {
-            let mut data = vec![];
-            let char_data = char_data.cast::<u8>();
-            for i in 0.. {
-                let next = char_data.offset(i).read()?;
-                if next == 0 {
-                    break;
-                }
-                data.push(next);
-            }
-            String::from_utf8(data)?
-        };
-        return Ok(if number == 0 {
-            base
-        } else {
-            format!("{base}_{}", number - 1)
-        });
+        return read_legacy_fname_entry(&entry);
     }
 
     let blocks = Ptr::<Ptr<u8>>::new(fnamepool + 0x10, ctx.clone())?;
@@ -208,6 +254,15 @@ pub fn resolve_fname(ctx: &Ctx, comparison_index: u32, number: u32) -> Result<St
     let is_wide = header & 1 != 0;
 
     let data = block.offset(offset + 2);
+
+    // numbered entry with outlined numbers: zero length header followed by the
+    // unaligned id of the base entry and the number
+    if ctx.outline_number && len == 0 {
+        let numbered = data.read_vec(8)?;
+        let id = u32::from_le_bytes(numbered[0..4].try_into().unwrap());
+        let number = u32::from_le_bytes(numbered[4..8].try_into().unwrap());
+        return resolve_fname(ctx, id, number);
+    }
     let base = if is_wide {
         String::from_utf16(
             &data
@@ -217,13 +272,9 @@ pub fn resolve_fname(ctx: &Ctx, comparison_index: u32, number: u32) -> Result<St
                 .collect::<Vec<_>>(),
         )?
     } else {
-        String::from_utf8(data.read_vec(len)?)?
+        decode_ansi(&data.read_vec(len)?)
     };
-    Ok(if number == 0 {
-        base
-    } else {
-        format!("{base}_{}", number - 1)
-    })
+    Ok(base)
 }
 
 pub fn extract_fnames(ctx: &Ctx) -> Result<BTreeMap<u32, String>> {
@@ -234,79 +285,99 @@ pub fn extract_fnames(ctx: &Ctx) -> Result<BTreeMap<u32, String>> {
     let case_preserving = ctx.case_preserving;
 
     if ue_version < (4, 22) {
-        anyhow::bail!("unimplemented fname extraction for < 4.22")
+        let names_array = ctx.read::<u64>(fname_pool_address)?;
+        let num_elements = ctx
+            .read::<i32>(names_array + LEGACY_NUM_ELEMENTS_OFFSET)?
+            .max(0) as u32;
+        let num_chunks = num_elements.div_ceil(LEGACY_NAMES_PER_CHUNK);
+
+        let chunks = ctx.read_vec::<u64>(names_array, num_chunks as usize)?;
+        for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+            if chunk == 0 {
+                continue;
+            }
+            let first = chunk_index as u32 * LEGACY_NAMES_PER_CHUNK;
+            let count = LEGACY_NAMES_PER_CHUNK.min(num_elements - first);
+            let entries = ctx.read_vec::<u64>(chunk, count as usize)?;
+            for (i, entry) in entries.into_iter().enumerate() {
+                // unused slots are null
+                if entry == 0 {
+                    continue;
+                }
+                let entry = Ptr::<()>::new(entry, ctx.clone())?;
+                names.insert(first + i as u32, read_legacy_fname_entry(&entry)?);
+            }
+        }
     } else {
-        let block_len = 0x20000;
-
-        let block_count = {
-            let mut buf = [0u8; 4];
-            ctx.read_buf(fname_pool_address + 8, &mut buf)?;
-            u32::from_le_bytes(buf) as usize
-        };
-
-        for block_index in 0..=block_count {
-            let block_ptr = {
-                let mut buf = [0u8; 8];
-                ctx.read_buf(
-                    fname_pool_address + 0x10 + (block_index as u64) * 8,
-                    &mut buf,
-                )?;
-                u64::from_le_bytes(buf)
+        // FNameEntryAllocator: FRWLock, CurrentBlock, CurrentByteCursor, Blocks[]
+        let current_block = ctx.read::<u32>(fname_pool_address + 8)? as usize;
+        let current_byte_cursor = ctx.read::<u32>(fname_pool_address + 0xc)? as usize;
+        let blocks = ctx.read_vec::<u64>(fname_pool_address + 0x10, current_block + 1)?;
+
+        // entries are aligned to alignof(FNameEntry), ids are offsets in units of that stride
+        let stride = if case_preserving { 4 } else { 2 };
+        // case preserving entries start with the FNameEntryId of the comparison entry
+        let header_offset = if case_preserving { 4 } else { 0 };
+
+        for (block_index, block_ptr) in blocks.into_iter().enumerate() {
+            let block_len = if block_index == current_block {
+                current_byte_cursor
+            } else {
+                stride << 16
             };
-
-            let mut chunk = vec![0u8; block_len];
-            if ctx.read_buf(block_ptr, &mut chunk).is_err() {
+            let mut block = vec![0u8; block_len];
+            if ctx.read_buf(block_ptr, &mut block).is_err() {
                 continue;
             }
 
-            let mut possible = vec![];
-            let mut i = 2;
-            let mut bytes_iter = chunk.iter().skip(2);
-
-            // assume FNames cannot contain chars < 32
-            while let Some(pos) = bytes_iter.position(|b| !(32..).contains(b)) {
-                possible.push((i - 2)..(i + pos + 1));
-                i += pos + 1;
-            }
-
-            // Validate potential FName entries in printable regions
-            for range in &possible {
-                let base = range.start;
-                let p = &chunk[range.clone()];
-                for i in 2..p.len() {
-                    let index = base + i - 2;
-
-                    // filter invalid alignment
-                    if case_preserving {
-                        if index % 4 != 0 {
-                            continue;
-                        }
-                    } else if index % 2 != 0 {
-                        continue;
+            let mut cursor = 0;
+            while cursor + header_offset + 2 <= block.len() {
+                let header =
+                    u16::from_le_bytes(block[cursor + header_offset..][..2].try_into().unwrap());
+                let len = if case_preserving {
+                    (header >> 1) as usize
+                } else {
+                    (header >> 6) as usize
+                };
+                let is_wide = (header & 1) != 0;
+
+                let id = ((block_index << 16) | (cursor / stride)) as u32;
+                let data_start = cursor + header_offset + 2;
+                let data_len = if len == 0 {
+                    // end of a full block is zero filled
+                    if !ctx.outline_number {
+                        break;
                     }
-
-                    let header = ((p[i - 1] as u16) << 8) + p[i - 2] as u16;
-
-                    let len = if case_preserving {
-                        (header >> 1) as usize
-                    } else {
-                        (header >> 6) as usize
+                    let Some(numbered) = block.get(data_start..data_start + 8) else {
+                        break;
                     };
-                    let is_wide = (header & 1) != 0;
-
-                    if len > 0
-                        && !is_wide
-                        && i + len < p.len()
-                        && let Ok(name) = String::from_utf8(p[i..(i + len)].to_vec())
-                    {
-                        let value = if case_preserving {
-                            ((block_index << 16) | (index / 4)) as u32
-                        } else {
-                            ((block_index << 16) | (index / 2)) as u32
-                        };
-                        names.insert(value, name);
+                    let base_id = u32::from_le_bytes(numbered[0..4].try_into().unwrap());
+                    let number = u32::from_le_bytes(numbered[4..8].try_into().unwrap());
+                    if base_id == 0 {
+                        break;
                     }
-                }
+                    names.insert(id, resolve_fname(ctx, base_id, number)?);
+                    8
+                } else {
+                    let data_len = if is_wide { len * 2 } else { len };
+                    let data = block
+                        .get(data_start..data_start + data_len)
+                        .with_context(|| format!("FName entry {id:#x} exceeds block"))?;
+                    let name = if is_wide {
+                        String::from_utf16(
+                            &data
+                                .chunks(2)
+                                .map(|chunk| u16::from_le_bytes(chunk.try_into().unwrap()))
+                                .collect::<Vec<_>>(),
+                        )
+                        .with_context(|| format!("invalid FName entry {id:#x}"))?
+                    } else {
+                        decode_ansi(data)
+                    };
+                    names.insert(id, name);
+                    data_len
+                };
+                cursor = (data_start + data_len).next_multiple_of(stride);
             }
         }
     }
@@ -314,6 +385,67 @@ pub fn extract_fnames(ctx: &Ctx) -> Result<BTreeMap<u32, String>> {
     Ok(names)
 }
 
+/// Whether the FNamePool (UE >= 4.22) holds numbered entries, which only exist when numbers
+/// are outlined (UE_FNAME_OUTLINE_NUMBER). Any running game has numbered names, so finding
+/// none means numbers are stored inline. Reads raw memory as no [`Ctx`] exists yet while the
+/// FName layout is being determined.
+pub fn fname_pool_has_numbered_entries(
+    mem: &impl Mem,
+    fname_pool_address: u64,
+    case_preserving: bool,
+) -> Result<bool> {
+    let read_u32 = |address: u64| -> Result<u32> {
+        let mut buf = [0; 4];
+        mem.read_buf(address, &mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    };
+    let current_block = read_u32(fname_pool_address + 8)? as usize;
+    let current_byte_cursor = read_u32(fname_pool_address + 0xc)? as usize;
+
+    let stride = if case_preserving { 4 } else { 2 };
+    let header_offset = if case_preserving { 4 } else { 0 };
+
+    for block_index in 0..=current_block {
+        let mut block_ptr = [0; 8];
+        mem.read_buf(
+            fname_pool_address + 0x10 + block_index as u64 * 8,
+            &mut block_ptr,
+        )?;
+        let block_len = if block_index == current_block {
+            current_byte_cursor
+        } else {
+            stride << 16
+        };
+        let mut block = vec![0u8; block_len];
+        mem.read_buf(u64::from_le_bytes(block_ptr), &mut block)
+            .with_context(|| format!("failed to read FName block {block_index}"))?;
+
+        let mut cursor = 0;
+        while cursor + header_offset + 2 <= block.len() {
+            let header =
+                u16::from_le_bytes(block[cursor + header_offset..][..2].try_into().unwrap());
+            let len = if case_preserving {
+                (header >> 1) as usize
+            } else {
+                (header >> 6) as usize
+            };
+            let data_start = cursor + header_offset + 2;
+            if len == 0 {
+                // a numbered entry references its base entry, the zero filled end of a
+                // full block does not
+                let base_id = block.get(data_start..data_start + 4);
+                if base_id.is_some_and(|id| id != [0; 4]) {
+                    return Ok(true);
+                }
+                break;
+            }
+            let data_len = if header & 1 != 0 { len * 2 } else { len };
+            cursor = (data_start + data_len).next_multiple_of(stride);
+        }
+    }
+    Ok(false)
+}
+
 // FScriptArray - untyped array for runtime element access
 #[derive(Debug, Clone, Copy)]
 pub struct FScriptArray;
@@ -326,6 +458,10 @@ impl Ptr<FScriptArray> {
         let offset = self.ctx().struct_member("FScriptArray", "ArrayNum");
         self.byte_offset(offset).cast()
     }
+    pub fn max(&self) -> Ptr<i32> {
+        let offset = self.ctx().struct_member("FScriptArray", "ArrayMax");
+        self.byte_offset(offset).cast()
+    }
 }
 
 // FScriptBitArray - for reading allocation flags in sparse arrays
@@ -0,0 +1,388 @@
+//! ELF core file (`gcore`, systemd-coredump) input, e.g. for games running under Proton
+
+use anyhow::{Context, Result, bail};
+use patternsleuth::image::Image;
+
+use crate::mem::Mem;
+use crate::{MemoryRegion, RegionMem};
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_FILE: u32 = 0x46494c45;
+
+pub struct OpenCoreFile {
+    pub mem: RegionMem<'static>,
+    pub image: Image<'static>,
+}
+
+/// Open an ELF core file, leaking the mmap to get `'static` lifetimes.
+pub fn open_core_file(path: impl AsRef<std::path::Path>) -> Result<OpenCoreFile> {
+    let file = std::fs::File::open(path)?;
+    let mmap: &'static memmap2::Mmap =
+        Box::leak(Box::new(unsafe { memmap2::MmapOptions::new().map(&file)? }));
+    let core = CoreFile::parse(mmap)?;
+
+    let mem = RegionMem::from_regions(
+        core.segments
+            .iter()
+            .filter(|s| !s.data.is_empty())
+            .map(|s| MemoryRegion::new(s.vaddr, s.data))
+            .collect(),
+    );
+
+    let base_address = find_game_module(&core)?;
+    let image = patternsleuth::image::pe::PEImage::read_inner_memory(
+        base_address as usize,
+        None::<&std::path::Path>,
+        false,
+        |address, size| {
+            let mut buf = vec![0; size];
+            mem.read_buf(address as u64, &mut buf).context(
+                "module memory missing from core, capture with /proc/<pid>/coredump_filter set to 0x3f",
+            )?;
+            Ok(buf)
+        },
+    )?;
+
+    Ok(OpenCoreFile { mem, image })
+}
+
+struct Segment<'a> {
+    vaddr: u64,
+    data: &'a [u8],
+}
+
+struct MappedFile {
+    start: u64,
+    file_offset: u64,
+    path: String,
+}
+
+struct CoreFile<'a> {
+    segments: Vec<Segment<'a>>,
+    files: Vec<MappedFile>,
+}
+
+impl<'a> CoreFile<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        if !data.starts_with(b"\x7fELF") {
+            bail!("not an ELF file");
+        }
+        // ELFCLASS64, ELFDATA2LSB
+        if data.get(4..6) != Some(&[2, 1][..]) {
+            bail!("only 64-bit little endian core files are supported");
+        }
+        // ET_CORE
+        if read_u16(data, 0x10)? != 4 {
+            bail!("ELF file is not a core file");
+        }
+
+        let phoff = read_u64(data, 0x20)? as usize;
+        let phentsize = read_u16(data, 0x36)? as usize;
+        let phnum = read_u16(data, 0x38)? as usize;
+
+        let mut segments = vec![];
+        let mut files = vec![];
+        for i in 0..phnum {
+            let ph = phoff + i * phentsize;
+            let p_type = read_u32(data, ph)?;
+            let offset = read_u64(data, ph + 0x8)? as usize;
+            let vaddr = read_u64(data, ph + 0x10)?;
+            let filesz = read_u64(data, ph + 0x20)? as usize;
+            let segment_data = data
+                .get(offset..offset + filesz)
+                .context("segment out of bounds, core file truncated?")?;
+            match p_type {
+                // memsz beyond filesz was not dumped so only filesz is usable
+                PT_LOAD => segments.push(Segment {
+                    vaddr,
+                    data: segment_data,
+                }),
+                PT_NOTE => files.extend(parse_file_notes(segment_data)?),
+                _ => {}
+            }
+        }
+
+        Ok(Self { segments, files })
+    }
+}
+
+/// Extract the mapped file list from the NT_FILE note
+fn parse_file_notes(notes: &[u8]) -> Result<Vec<MappedFile>> {
+    let align = |n: usize| (n + 3) & !3;
+
+    let mut files = vec![];
+    let mut pos = 0;
+    while pos + 12 <= notes.len() {
+        let namesz = read_u32(notes, pos)? as usize;
+        let descsz = read_u32(notes, pos + 4)? as usize;
+        let n_type = read_u32(notes, pos + 8)?;
+        let desc = pos + 12 + align(namesz);
+        pos = desc + align(descsz);
+
+        if n_type != NT_FILE {
+            continue;
+        }
+        let desc = notes.get(desc..desc + descsz).context("truncated note")?;
+        let count = read_u64(desc, 0)? as usize;
+        let page_size = read_u64(desc, 8)?;
+        let mut names = desc
+            .get(16 + count * 24..)
+            .context("truncated NT_FILE note")?
+            .split(|b| *b == 0);
+        for i in 0..count {
+            let entry = 16 + i * 24;
+            files.push(MappedFile {
+                start: read_u64(desc, entry)?,
+                file_offset: read_u64(desc, entry + 16)? * page_size,
+                path: String::from_utf8_lossy(names.next().unwrap_or_default()).into_owned(),
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// Find the base address of the game executable. PE images mapped from a `.exe` are
+/// preferred over other modules, the largest image wins.
+fn find_game_module(core: &CoreFile) -> Result<u64> {
+    let mut candidates = vec![];
+    for segment in &core.segments {
+        let Some(size) = pe_image_size(segment.data) else {
+            continue;
+        };
+        let is_exe = core.files.iter().any(|f| {
+            f.start == segment.vaddr
+                && f.file_offset == 0
+                && f.path.to_ascii_lowercase().ends_with(".exe")
+        });
+        candidates.push((is_exe, size, segment.vaddr));
+    }
+    let (_, _, base_address) = candidates
+        .into_iter()
+        .max()
+        .context("no PE image found in core file")?;
+    Ok(base_address)
+}
+
+/// SizeOfImage if `data` starts with a PE image header
+fn pe_image_size(data: &[u8]) -> Option<u32> {
+    if !data.starts_with(b"MZ") {
+        return None;
+    }
+    let pe = read_u32(data, 0x3c).ok()? as usize;
+    if data.get(pe..pe + 4) != Some(&b"PE\0\0"[..]) {
+        return None;
+    }
+    // COFF header is 0x14 bytes, SizeOfImage is at 0x38 in the optional header
+    read_u32(data, pe + 4 + 0x14 + 0x38).ok()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    Ok(u16::from_le_bytes(
+        data.get(offset..offset + 2)
+            .context("read out of bounds")?
+            .try_into()?,
+    ))
+}
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes(
+        data.get(offset..offset + 4)
+            .context("read out of bounds")?
+            .try_into()?,
+    ))
+}
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    Ok(u64::from_le_bytes(
+        data.get(offset..offset + 8)
+            .context("read out of bounds")?
+            .try_into()?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PHDR_SIZE: usize = 0x38;
+
+    fn push_u32(buf: &mut Vec<u8>, value: u32) {
+        buf.extend(value.to_le_bytes());
+    }
+    fn push_u64(buf: &mut Vec<u8>, value: u64) {
+        buf.extend(value.to_le_bytes());
+    }
+
+    /// Minimal PE header with only SizeOfImage set
+    fn pe_image(size_of_image: u32) -> Vec<u8> {
+        let mut data = vec![0; 0x100];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        data[0x80..0x84].copy_from_slice(b"PE\0\0");
+        data[0x80 + 4 + 0x14 + 0x38..][..4].copy_from_slice(&size_of_image.to_le_bytes());
+        data
+    }
+
+    /// NT_FILE note mapping (start, end, page offset, path)
+    fn file_note(files: &[(u64, u64, u64, &str)]) -> Vec<u8> {
+        let mut desc = vec![];
+        push_u64(&mut desc, files.len() as u64);
+        push_u64(&mut desc, 0x1000);
+        for (start, end, page_offset, _) in files {
+            push_u64(&mut desc, *start);
+            push_u64(&mut desc, *end);
+            push_u64(&mut desc, *page_offset);
+        }
+        for (_, _, _, path) in files {
+            desc.extend(path.as_bytes());
+            desc.push(0);
+        }
+        while desc.len() % 4 != 0 {
+            desc.push(0);
+        }
+
+        let mut note = vec![];
+        push_u32(&mut note, 5);
+        push_u32(&mut note, desc.len() as u32);
+        push_u32(&mut note, NT_FILE);
+        note.extend(b"CORE\0\0\0\0");
+        note.extend(desc);
+        note
+    }
+
+    /// ELF core with one program header per (type, vaddr, data)
+    fn core(segments: &[(u32, u64, &[u8])]) -> Vec<u8> {
+        let mut data = vec![0; 0x40];
+        data[..4].copy_from_slice(b"\x7fELF");
+        data[4..6].copy_from_slice(&[2, 1]);
+        data[0x10..0x12].copy_from_slice(&4u16.to_le_bytes());
+        data[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes());
+        data[0x36..0x38].copy_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        data[0x38..0x3a].copy_from_slice(&(segments.len() as u16).to_le_bytes());
+
+        let mut offset = 0x40 + segments.len() * PHDR_SIZE;
+        for (p_type, vaddr, segment) in segments {
+            push_u32(&mut data, *p_type);
+            push_u32(&mut data, 0);
+            push_u64(&mut data, offset as u64);
+            push_u64(&mut data, *vaddr);
+            push_u64(&mut data, 0);
+            push_u64(&mut data, segment.len() as u64);
+            // memsz larger than filesz as for partially dumped segments
+            push_u64(&mut data, segment.len() as u64 + 0x1000);
+            push_u64(&mut data, 0x1000);
+            offset += segment.len();
+        }
+        for (_, _, segment) in segments {
+            data.extend(*segment);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_segments() -> Result<()> {
+        let note = file_note(&[]);
+        let data = core(&[
+            (PT_LOAD, 0x10000, &[1, 2, 3, 4]),
+            (PT_NOTE, 0, &note),
+            (PT_LOAD, 0x20000, &[5, 6]),
+        ]);
+        let core = CoreFile::parse(&data)?;
+
+        let segments: Vec<_> = core.segments.iter().map(|s| (s.vaddr, s.data)).collect();
+        assert_eq!(
+            segments,
+            [(0x10000, &[1, 2, 3, 4][..]), (0x20000, &[5, 6][..])]
+        );
+        assert!(core.files.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rejects_other_files() {
+        let mut data = core(&[]);
+        data[0x10] = 2; // ET_EXEC
+        assert!(CoreFile::parse(&data).is_err());
+        data[4] = 1; // ELFCLASS32
+        assert!(CoreFile::parse(&data).is_err());
+        assert!(CoreFile::parse(b"MZ").is_err());
+
+        // segment beyond the end of the file
+        let mut data = core(&[(PT_LOAD, 0x10000, &[0; 0x10])]);
+        data.truncate(data.len() - 1);
+        assert!(CoreFile::parse(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_file_notes() -> Result<()> {
+        let mut notes = vec![];
+        // unrelated notes before NT_FILE are skipped
+        push_u32(&mut notes, 5);
+        push_u32(&mut notes, 3);
+        push_u32(&mut notes, 1); // NT_PRSTATUS
+        notes.extend(b"CORE\0\0\0\0\0\0\0\0");
+        notes.extend(file_note(&[
+            (0x140000000, 0x140001000, 0, "/games/Game.exe"),
+            (0x140001000, 0x140005000, 2, "/games/Game.exe"),
+            (0x7f0000000000, 0x7f0000001000, 0, "/usr/lib/libc.so.6"),
+        ]));
+
+        let files: Vec<_> = parse_file_notes(&notes)?
+            .into_iter()
+            .map(|f| (f.start, f.file_offset, f.path))
+            .collect();
+        assert_eq!(
+            files,
+            [
+                (0x140000000, 0, "/games/Game.exe".into()),
+                (0x140001000, 0x2000, "/games/Game.exe".into()),
+                (0x7f0000000000, 0, "/usr/lib/libc.so.6".into()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pe_image_size() {
+        assert_eq!(pe_image_size(&pe_image(0x5000)), Some(0x5000));
+        assert_eq!(pe_image_size(&[0; 0x100]), None);
+
+        let mut not_pe = pe_image(0x5000);
+        not_pe[0x80] = b'N';
+        assert_eq!(pe_image_size(&not_pe), None);
+        // header cut off before SizeOfImage
+        assert_eq!(pe_image_size(&pe_image(0x5000)[..0xc0]), None);
+    }
+
+    #[test]
+    fn test_find_game_module() -> Result<()> {
+        let big_dll = pe_image(0x900000);
+        let exe = pe_image(0x5000);
+        let other_exe = pe_image(0x3000);
+        let note = file_note(&[
+            (0x180000000, 0x180001000, 0, "/windows/system32/big.dll"),
+            (0x140000000, 0x140001000, 0, "/games/Game.exe"),
+            // mapped from the middle of the file so not the image base
+            (0x150000000, 0x150001000, 1, "/games/Other.EXE"),
+        ]);
+        let data = core(&[
+            (PT_NOTE, 0, &note),
+            (PT_LOAD, 0x180000000, &big_dll),
+            (PT_LOAD, 0x140000000, &exe),
+            (PT_LOAD, 0x150000000, &other_exe),
+        ]);
+        // the .exe wins over the larger dll
+        assert_eq!(find_game_module(&CoreFile::parse(&data)?)?, 0x140000000);
+
+        // without file notes the largest image is used
+        let data = core(&[
+            (PT_LOAD, 0x140000000, &exe),
+            (PT_LOAD, 0x180000000, &big_dll),
+            (PT_LOAD, 0x10000, &[0; 0x10]),
+        ]);
+        assert_eq!(find_game_module(&CoreFile::parse(&data)?)?, 0x180000000);
+
+        let data = core(&[(PT_LOAD, 0x10000, &[0; 0x10])]);
+        assert!(find_game_module(&CoreFile::parse(&data)?).is_err());
+        Ok(())
+    }
+}
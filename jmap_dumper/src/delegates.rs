@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use jmap::DelegatePropertyValue;
+
+use crate::containers::{FName, FScriptMap, TArray};
+use crate::mem::Ptr;
+use crate::object_ptrs::FWeakObjectPtr;
+use crate::objects::UObject;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FScriptDelegate;
+impl Ptr<FScriptDelegate> {
+    pub fn object(&self) -> Ptr<FWeakObjectPtr> {
+        let offset = self.ctx().struct_member("FScriptDelegate", "Object");
+        self.byte_offset(offset).cast()
+    }
+    pub fn function_name(&self) -> Ptr<FName> {
+        let offset = self.ctx().struct_member("FScriptDelegate", "FunctionName");
+        self.byte_offset(offset).cast()
+    }
+    /// Read the binding, None if the delegate is unbound
+    pub fn read(&self) -> Result<Option<DelegatePropertyValue>> {
+        let function_name = self.function_name().read()?;
+        if function_name == "None" {
+            return Ok(None);
+        }
+        Ok(Some(DelegatePropertyValue {
+            object: self.object().read()?,
+            function_name,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FMulticastScriptDelegate;
+impl Ptr<FMulticastScriptDelegate> {
+    pub fn invocation_list(&self) -> Ptr<TArray<FScriptDelegate>> {
+        let offset = self
+            .ctx()
+            .struct_member("FMulticastScriptDelegate", "InvocationList");
+        self.byte_offset(offset).cast()
+    }
+    pub fn read(&self) -> Result<Vec<DelegatePropertyValue>> {
+        let list = self.invocation_list();
+        let num = list.len()?;
+        let mut bindings = Vec::with_capacity(num);
+        if let Some(data) = list.data()? {
+            let size = self.ctx().get_struct("FScriptDelegate").size as usize;
+            for i in 0..num {
+                let delegate = data.byte_offset(i * size);
+                if let Some(binding) = delegate.read()? {
+                    bindings.push(binding);
+                }
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+/// `FSparseDelegate` only stores whether it is bound, the actual
+/// `FMulticastScriptDelegate` lives in `FSparseDelegateStorage::SparseDelegates`
+/// keyed by owning object and delegate name
+#[derive(Debug, Clone, Copy)]
+pub struct FSparseDelegate;
+impl Ptr<FSparseDelegate> {
+    pub fn is_bound(&self) -> Result<bool> {
+        Ok(self.cast::<u8>().read()? != 0)
+    }
+}
+
+/// Contents of `FSparseDelegateStorage::SparseDelegates`: bindings by owner address and
+/// delegate name. Read once per dump rather than searching the map for every property.
+#[derive(Debug, Default)]
+pub struct SparseDelegates(HashMap<u64, HashMap<String, Vec<DelegatePropertyValue>>>);
+impl SparseDelegates {
+    pub fn read(storage: &Ptr<FScriptMap>) -> Result<Self> {
+        let ctx = storage.ctx();
+        let object_size = ctx.get_struct("FSparseDelegateObjectElement").size as usize;
+        let delegate_size = ctx.get_struct("FSparseDelegateElement").size as usize;
+
+        let mut owners = HashMap::new();
+        let objects = storage.pairs().elements();
+        for i in 0..objects.get_max_index()? {
+            if !objects.is_valid_index(i)? {
+                continue;
+            }
+            let entry = objects
+                .get_data(i, object_size)?
+                .cast::<FSparseDelegateObjectElement>();
+            let Some(owner) = entry.object().read()? else {
+                continue;
+            };
+
+            let mut bindings = HashMap::new();
+            let delegates = entry.delegates().pairs().elements();
+            for j in 0..delegates.get_max_index()? {
+                if !delegates.is_valid_index(j)? {
+                    continue;
+                }
+                let entry = delegates
+                    .get_data(j, delegate_size)?
+                    .cast::<FSparseDelegateElement>();
+                let delegate = match entry.delegate().read()? {
+                    Some(delegate) => delegate.read()?,
+                    None => vec![],
+                };
+                bindings.insert(entry.delegate_name().read()?, delegate);
+            }
+            owners.insert(owner.address(), bindings);
+        }
+        Ok(Self(owners))
+    }
+
+    /// Bindings of sparse delegate `delegate_name` of the object at `owner`, None if the
+    /// storage has no entry for it
+    pub fn get(&self, owner: u64, delegate_name: &str) -> Option<&[DelegatePropertyValue]> {
+        self.0
+            .get(&owner)
+            .and_then(|delegates| delegates.get(delegate_name))
+            .map(Vec::as_slice)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FSparseDelegateObjectElement;
+impl Ptr<FSparseDelegateObjectElement> {
+    pub fn object(&self) -> Ptr<Option<Ptr<UObject>>> {
+        let offset = self
+            .ctx()
+            .struct_member("FSparseDelegateObjectElement", "Object");
+        self.byte_offset(offset).cast()
+    }
+    pub fn delegates(&self) -> Ptr<FScriptMap> {
+        let offset = self
+            .ctx()
+            .struct_member("FSparseDelegateObjectElement", "Delegates");
+        self.byte_offset(offset).cast()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FSparseDelegateElement;
+impl Ptr<FSparseDelegateElement> {
+    pub fn delegate_name(&self) -> Ptr<FName> {
+        let offset = self
+            .ctx()
+            .struct_member("FSparseDelegateElement", "DelegateName");
+        self.byte_offset(offset).cast()
+    }
+    pub fn delegate(&self) -> Ptr<Option<Ptr<FMulticastScriptDelegate>>> {
+        let offset = self
+            .ctx()
+            .struct_member("FSparseDelegateElement", "Delegate");
+        self.byte_offset(offset).cast()
+    }
+}
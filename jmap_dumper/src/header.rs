@@ -4,13 +4,14 @@ use std::fmt::Write;
 use jmap::{
     EClassCastFlags, EFunctionFlags, EPropertyFlags, Function, Jmap, ObjectType, Property,
     PropertyType, Struct,
+    index::{JmapIndex, ObjectPath},
 };
 
 type Objects = BTreeMap<String, ObjectType>;
 
 fn get_class_name(objects: &Objects, path: &str) -> String {
     let obj = &objects[path];
-    let name = path.rsplit(['/', '.', ':']).next().unwrap();
+    let name = ObjectPath::parse(path).name();
     match obj {
         ObjectType::Enum(_) => name.into(),
         ObjectType::ScriptStruct(_) => format!("F{name}"),
@@ -30,31 +31,16 @@ fn get_class_name(objects: &Objects, path: &str) -> String {
     }
 }
 
-fn get_class_functions<'a>(
-    objects: &'a Objects,
-    class_obj: &'a Struct,
-) -> Vec<(&'a String, &'a Function)> {
-    class_obj
-        .object
-        .children
-        .iter()
-        .filter_map(|child_path| {
-            if let Some(ObjectType::Function(func)) = objects.get(child_path) {
-                Some((child_path, func))
-            } else {
-                None
-            }
-        })
-        .collect()
-}
-
 fn property_type_name(objects: &Objects, prop: &Property) -> String {
     match &prop.r#type {
         PropertyType::Struct { r#struct } => get_class_name(objects, r#struct),
         PropertyType::Str => "FString".into(),
         PropertyType::Name => "FName".into(),
         PropertyType::Text => "FText".into(),
-        PropertyType::FieldPath => "FFieldPath".into(),
+        PropertyType::FieldPath { property_class } => match property_class {
+            Some(c) => format!("TFieldPath<F{c}>"),
+            None => "FFieldPath".into(),
+        },
         PropertyType::MulticastInlineDelegate { .. } => "FMulticastInlineDelegate".into(),
         PropertyType::MulticastSparseDelegate { .. } => "FMulticastSparseDelegate".into(),
         PropertyType::MulticastDelegate { .. } => "FMulticastDelegate".into(),
@@ -122,7 +108,7 @@ fn property_type_name(objects: &Objects, prop: &Property) -> String {
 }
 
 fn generate_function(buffer: &mut String, objects: &Objects, path: &str, func: &Function) {
-    let name = path.rsplit(['/', '.', ':']).next().unwrap();
+    let name = ObjectPath::parse(path).name();
 
     // Find return type (property with CPF_ReturnParm)
     let return_prop = func
@@ -189,17 +175,18 @@ fn generate_function(buffer: &mut String, objects: &Objects, path: &str, func: &
 
 fn generate_struct_or_class(
     buffer: &mut String,
-    objects: &Objects,
+    index: &JmapIndex<'_>,
     path: &str,
     struct_obj: &Struct,
     keyword: &str,
 ) {
+    let objects = &index.jmap().objects;
     let name = get_class_name(objects, path);
 
     writeln!(buffer, "// Size: 0x{:x}", struct_obj.properties_size).unwrap();
     write!(buffer, "{} {}", keyword, name).unwrap();
 
-    if let Some(super_path) = &struct_obj.super_struct {
+    if let Some((super_path, _)) = index.super_chain(path).nth(1) {
         let super_name = get_class_name(objects, super_path);
         write!(buffer, " : public {}", super_name).unwrap();
     }
@@ -222,8 +209,8 @@ fn generate_struct_or_class(
         .unwrap();
     }
 
-    let functions = get_class_functions(objects, struct_obj);
-    if !functions.is_empty() {
+    let mut functions = index.functions(path).peekable();
+    if functions.peek().is_some() {
         writeln!(buffer).unwrap();
         for (func_path, func) in functions {
             generate_function(buffer, objects, func_path, func);
@@ -238,6 +225,7 @@ pub fn into_header(reflection_data: &Jmap) -> String {
     let mut buffer = String::new();
 
     let objects = &reflection_data.objects;
+    let index = reflection_data.index();
 
     for (path, obj) in objects {
         match obj {
@@ -254,14 +242,14 @@ pub fn into_header(reflection_data: &Jmap) -> String {
             ObjectType::ScriptStruct(script_struct) => {
                 generate_struct_or_class(
                     &mut buffer,
-                    objects,
+                    &index,
                     path,
                     &script_struct.r#struct,
                     "struct",
                 );
             }
             ObjectType::Class(class) => {
-                generate_struct_or_class(&mut buffer, objects, path, &class.r#struct, "class");
+                generate_struct_or_class(&mut buffer, &index, path, &class.r#struct, "class");
             }
             _ => {}
         }
@@ -1,9 +1,15 @@
 pub mod containers;
+pub mod core_file;
+pub mod delegates;
 mod header;
 pub mod mem;
+pub mod object_ptrs;
 pub mod objects;
 mod proc_name;
+mod resolvers;
+pub mod snapshot;
 pub mod structs;
+pub mod text;
 mod vtable;
 
 pub use header::into_header;
@@ -15,7 +21,7 @@ use std::sync::Arc;
 use anyhow::{Context, Result, bail};
 use containers::{FName, FScriptMap, FScriptSet, FString};
 use jmap::{
-    BytePropertyValue, Class, EClassCastFlags, EObjectFlags, EngineVersion, Enum,
+    BytePropertyValue, Class, Diagnostic, EClassCastFlags, EObjectFlags, EngineVersion, Enum,
     EnumPropertyValue, Function, Jmap, Metadata, Object, ObjectType, Package, Property,
     PropertyType, PropertyValue, ScriptStruct, Struct,
 };
@@ -23,48 +29,65 @@ use mem::{Ctx, MemCache, ProcessHandle, Ptr};
 use objects::FOptionalProperty;
 use ordermap::OrderMap;
 use patternsleuth::image::Image;
-use patternsleuth::resolvers::{impl_collector, impl_try_collector, resolve};
+use patternsleuth::resolvers::{impl_collector, resolve};
+use serde::{Deserialize, Serialize};
 
 use crate::containers::{FUtf8String, extract_fnames};
+use crate::delegates::{
+    FMulticastScriptDelegate, FScriptDelegate, FSparseDelegate, SparseDelegates,
+};
+use crate::object_ptrs::{
+    FFieldPath, FLazyObjectPtr, FScriptInterface, FSoftObjectPtr, FWeakObjectPtr,
+};
 use crate::objects::{
     FUObjectArray, UClass, UEnum, UFunction, UObject, UScriptStruct, UStruct, ZArrayProperty,
     ZBoolProperty, ZByteProperty, ZClassProperty, ZDelegateProperty, ZEnumProperty,
-    ZInterfaceProperty, ZLazyObjectProperty, ZMapProperty, ZMulticastDelegateProperty,
-    ZObjectProperty, ZProperty, ZSetProperty, ZSoftClassProperty, ZSoftObjectProperty,
-    ZStructProperty, ZWeakObjectProperty,
+    ZFieldPathProperty, ZInterfaceProperty, ZLazyObjectProperty, ZMapProperty,
+    ZMulticastDelegateProperty, ZObjectProperty, ZProperty, ZSetProperty, ZSoftClassProperty,
+    ZSoftObjectProperty, ZStructProperty, ZWeakObjectProperty,
 };
 use crate::structs::Structs;
+use crate::text::FText;
 
-impl_try_collector! {
+impl_collector! {
     #[derive(Debug, PartialEq, Clone)]
     struct Resolution {
         guobject_array: patternsleuth::resolvers::unreal::guobject_array::GUObjectArray,
         fname_pool: patternsleuth::resolvers::unreal::fname::FNamePool,
         engine_version: patternsleuth::resolvers::unreal::engine_version::EngineVersion,
-        opt: OptResolution,
-    }
-}
-
-impl_collector! {
-    #[derive(Debug, PartialEq, Clone)]
-    struct OptResolution {
         build: patternsleuth::resolvers::unreal::engine_version::BuildChangeList,
         fname_constant: patternsleuth::resolvers::unreal::fname::StaticFNameConst,
+        sparse_delegate_storage: resolvers::SparseDelegateStorage,
     }
 }
 
 fn read_path(obj: &Ptr<UObject>) -> Result<String> {
-    let mut objects = vec![obj.clone()];
+    let ctx = obj.ctx();
+    let caching = ctx.caching();
+    let cached = |obj: &Ptr<UObject>| {
+        caching
+            .then(|| ctx.path_cache.read().unwrap().get(&obj.address()).cloned())
+            .flatten()
+    };
 
-    let mut obj = obj.clone();
-    while let Some(outer) = obj.outer_private().read()? {
-        objects.push(outer.clone());
-        obj = outer;
+    // walk outers until reaching the root or an object with an already known path
+    let mut objects = vec![];
+    let mut path = None;
+    let mut obj = Some(obj.clone());
+    while let Some(o) = obj {
+        if let Some(p) = cached(&o) {
+            path = Some((p, o));
+            break;
+        }
+        obj = o.outer_private().read()?;
+        objects.push(o);
     }
 
-    let mut path = String::new();
-    let mut prev: Option<&Ptr<UObject>> = None;
-    for obj in objects.iter().rev() {
+    let (mut path, mut prev) = match path {
+        Some((path, prev)) => (path, Some(prev)),
+        None => (String::new(), None),
+    };
+    for obj in objects.into_iter().rev() {
         if let Some(prev) = prev {
             let sep = if prev
                 .class_private()
@@ -80,6 +103,12 @@ fn read_path(obj: &Ptr<UObject>) -> Result<String> {
             path.push(sep);
         }
         path.push_str(&obj.name_private().read()?);
+        if caching {
+            ctx.path_cache
+                .write()
+                .unwrap()
+                .insert(obj.address(), path.clone());
+        }
         prev = Some(obj);
     }
 
@@ -92,14 +121,30 @@ pub struct MemoryRegion<'a> {
     end_address: u64,
     data: &'a [u8],
 }
+impl<'a> MemoryRegion<'a> {
+    pub fn new(base_address: u64, data: &'a [u8]) -> Self {
+        Self {
+            base_address,
+            end_address: base_address + data.len() as u64,
+            data,
+        }
+    }
+}
 
+/// Memory backed by a set of captured regions (minidump, core file)
 #[derive(Clone)]
-pub struct MinidumpMem<'a> {
+pub struct RegionMem<'a> {
     regions: Arc<Vec<MemoryRegion<'a>>>,
 }
 
-impl<'a> MinidumpMem<'a> {
-    pub fn new(minidump: &'a minidump::Minidump<'_, &'a [u8]>) -> Result<Self> {
+impl<'a> RegionMem<'a> {
+    pub fn from_regions(mut regions: Vec<MemoryRegion<'a>>) -> Self {
+        regions.sort_by_key(|r| r.base_address);
+        Self {
+            regions: Arc::new(regions),
+        }
+    }
+    pub fn from_minidump(minidump: &'a minidump::Minidump<'_, &'a [u8]>) -> Result<Self> {
         use minidump::UnifiedMemory;
 
         let mut regions = Vec::new();
@@ -115,20 +160,11 @@ impl<'a> MinidumpMem<'a> {
             };
 
             if !bytes.is_empty() {
-                let end_address = base_address + bytes.len() as u64;
-                regions.push(MemoryRegion {
-                    base_address,
-                    end_address,
-                    data: bytes,
-                });
+                regions.push(MemoryRegion::new(base_address, bytes));
             }
         }
 
-        regions.sort_by_key(|r| r.base_address);
-
-        Ok(MinidumpMem {
-            regions: Arc::new(regions),
-        })
+        Ok(Self::from_regions(regions))
     }
 }
 
@@ -148,7 +184,7 @@ pub fn open_minidump(path: impl AsRef<std::path::Path>) -> Result<OpenMinidump>
     Ok(OpenMinidump { minidump, image })
 }
 
-impl mem::Mem for MinidumpMem<'_> {
+impl mem::Mem for RegionMem<'_> {
     fn read_buf(&self, address: u64, buf: &mut [u8]) -> Result<()> {
         let mut bytes_read = 0;
         let total_bytes = buf.len();
@@ -209,6 +245,9 @@ impl mem::Mem for MinidumpMem<'_> {
 pub enum Input {
     Process(i32),
     Dump(PathBuf),
+    CoreFile(PathBuf),
+    /// Replay a snapshot written via [`DumpOptions::write_snapshot`]
+    Snapshot(PathBuf),
 }
 
 #[derive(Default)]
@@ -217,6 +256,14 @@ pub struct DumpOptions {
     pub all: bool,
     /// Dump FName table
     pub names: bool,
+    /// Record errors as diagnostics and continue instead of aborting the dump
+    pub lenient: bool,
+    /// Number of worker threads reading objects, 0 to use all available cores
+    pub threads: usize,
+    /// Overrides for values normally resolved from the image
+    pub connect: ConnectOptions,
+    /// Write all memory read during the dump to a snapshot which can be replayed via [`Input::Snapshot`]
+    pub write_snapshot: Option<PathBuf>,
 }
 
 pub fn dump(input: Input, struct_info: Option<Structs>, options: DumpOptions) -> Result<Jmap> {
@@ -227,14 +274,29 @@ pub fn dump(input: Input, struct_info: Option<Structs>, options: DumpOptions) ->
             let handle: ProcessHandle = ProcessHandle::new(pid);
             let mem = MemCache::wrap(handle);
             let image = patternsleuth::process::external::read_image_from_pid(pid)?;
-            dump_inner(mem, &image, struct_info, &source_name, options)
+            let ctx = connect(mem, &image, struct_info, &options.connect)?;
+            dump_inner(ctx, &source_name, options)
         }
         Input::Dump(path) => {
             let source_name = path.file_name().unwrap_or_default().to_string_lossy();
 
             let dump = open_minidump(&path)?;
-            let mem = MinidumpMem::new(dump.minidump)?;
-            dump_inner(mem, &dump.image, struct_info, &source_name, options)
+            // cached so touched pages can be written to a snapshot
+            let mem = MemCache::wrap(RegionMem::from_minidump(dump.minidump)?);
+            let ctx = connect(mem, &dump.image, struct_info, &options.connect)?;
+            dump_inner(ctx, &source_name, options)
+        }
+        Input::CoreFile(path) => {
+            let source_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+            let core = core_file::open_core_file(&path)?;
+            let mem = MemCache::wrap(core.mem);
+            let ctx = connect(mem, &core.image, struct_info, &options.connect)?;
+            dump_inner(ctx, &source_name, options)
+        }
+        Input::Snapshot(path) => {
+            let (ctx, source_name) = snapshot::connect_snapshot(&path, struct_info)?;
+            dump_inner(ctx, &source_name, options)
         }
     }
 }
@@ -259,91 +321,214 @@ pub fn connect_pid(pid: i32, struct_info: Option<Structs>) -> Result<Ctx> {
     let handle: ProcessHandle = ProcessHandle::new(pid);
     let mem = MemCache::wrap(handle);
     let image = patternsleuth::process::external::read_image_from_pid(pid)?;
-    connect(mem, &image, struct_info)
+    connect(mem, &image, struct_info, &Default::default())
 }
 
 pub fn connect_pid_live(pid: i32, struct_info: Option<Structs>) -> Result<Ctx> {
     let handle: ProcessHandle = ProcessHandle::new(pid);
     let image = patternsleuth::process::external::read_image_from_pid(pid)?;
-    connect(handle, &image, struct_info)
+    connect(handle, &image, struct_info, &Default::default())
+}
+
+/// Values overriding or supplementing what is resolved from the image, for when
+/// resolution fails or is ambiguous
+#[derive(Debug, Default, Clone)]
+pub struct ConnectOptions {
+    /// Engine version as (major, minor)
+    pub engine_version: Option<(u16, u16)>,
+    /// Address of FNamePool (GNames before 4.22)
+    pub fname_pool: Option<u64>,
+    /// Address of GUObjectArray
+    pub guobject_array: Option<u64>,
+    /// Whether the build uses case preserving names (WITH_CASE_PRESERVING_NAME)
+    pub case_preserving: Option<bool>,
+    /// Whether FName numbers are outlined into separate name entries (UE_FNAME_OUTLINE_NUMBER)
+    pub outline_number: Option<bool>,
+    /// Address of a static FName constant used to detect the FName layout
+    pub fname_constant: Option<u64>,
+    /// Address of `FSparseDelegateStorage::SparseDelegates`
+    pub sparse_delegate_storage: Option<u64>,
+}
+
+impl ConnectOptions {
+    /// Whether all required values are provided so image analysis can be skipped
+    fn skips_resolution(&self) -> bool {
+        self.engine_version.is_some()
+            && self.fname_pool.is_some()
+            && self.guobject_array.is_some()
+            && self.case_preserving.is_some()
+    }
+}
+
+/// Point failed or ambiguous resolutions at the option overriding them
+fn or_override<T, E>(result: Result<T, E>, name: &str, flag: &str) -> Result<T>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    result.with_context(|| {
+        format!("failed to resolve {name}, if multiple candidates were found pass the correct one via {flag}")
+    })
 }
 
 pub fn connect(
     mem: impl mem::Mem + 'static,
     image: &Image<'_>,
     struct_info: Option<Structs>,
+    options: &ConnectOptions,
 ) -> Result<Ctx> {
-    let results = resolve(image, Resolution::resolver())?;
-    println!("{results:X?}");
-
-    let fnamepool = results.fname_pool.0;
-
-    let version = (results.engine_version.major, results.engine_version.minor);
-
-    let mut case_preserving = false;
-
-    if let Ok(fname_constant) = &results.opt.fname_constant {
-        let name_constant_address = fname_constant.0;
-        let read_u32 = |addr: u64| -> Result<u32> {
-            let mut buf = [0u8; 4];
-            mem.read_buf(addr, &mut buf)?;
-            Ok(u32::from_le_bytes(buf))
-        };
-
-        // Field offsets mirror the FName layout in jmap_dumper/unreal/src/unreal.gs:
-        //         UE <  4.23: [CMP, NUM]
-        // 4.23 <= UE <  5.01: [CMP, DISP?, NUM]   (DISP only when case-preserving)
-        //         UE >= 5.01: [CMP, NUM, DISP?]
-        let comparison_index = read_u32(name_constant_address)?;
-        assert_ne!(comparison_index, 0);
-
-        let (case_preserving_detected, number_off) = if version < (4, 23) {
-            (false, 4)
-        } else if version < (5, 1) {
-            if read_u32(name_constant_address + 4)? == comparison_index {
-                (true, 8)
-            } else {
-                (false, 4)
-            }
+    let (guobject_array, fname_pool, engine_version, build, fname_constant, sparse_delegates) =
+        if options.skips_resolution() {
+            (None, None, None, None, None, None)
         } else {
-            let cp = read_u32(name_constant_address + 8)? == comparison_index;
-            (cp, 4)
+            let results = resolve(image, Resolution::resolver())?;
+            println!("{results:X?}");
+            (
+                Some(results.guobject_array),
+                Some(results.fname_pool),
+                Some(results.engine_version),
+                results.build.ok(),
+                results.fname_constant.ok(),
+                results.sparse_delegate_storage.ok(),
+            )
         };
 
-        let number = read_u32(name_constant_address + number_off)?;
-        assert_eq!(
-            number, 0,
-            "Builds with outlined name number (UE_FNAME_OUTLINE_NUMBER=1) are not supported"
-        );
-        case_preserving = case_preserving_detected;
+    let uobjectarray = match (options.guobject_array, guobject_array) {
+        (Some(address), _) => address,
+        (None, Some(r)) => or_override(r, "GUObjectArray", "--guobject-array")?.0,
+        (None, None) => unreachable!("resolution is only skipped if overridden"),
+    };
+    let fnamepool = match (options.fname_pool, fname_pool) {
+        (Some(address), _) => address,
+        (None, Some(r)) => or_override(r, "FNamePool", "--fname-pool")?.0,
+        (None, None) => unreachable!("resolution is only skipped if overridden"),
+    };
+    let version = match (options.engine_version, engine_version) {
+        (Some(version), _) => version,
+        (None, Some(r)) => {
+            let version = or_override(r, "EngineVersion", "--engine-version")?;
+            (version.major, version.minor)
+        }
+        (None, None) => unreachable!("resolution is only skipped if overridden"),
+    };
+
+    let mut case_preserving = options.case_preserving.unwrap_or(false);
+    let mut outline_number = options.outline_number;
+
+    let fname_constant = options.fname_constant.or(fname_constant.map(|c| c.0));
+    let needs_detection = options.case_preserving.is_none() || options.outline_number.is_none();
+    if let (true, Some(name_constant_address)) = (needs_detection, fname_constant) {
+        let mut words = [0u8; 12];
+        mem.read_buf(name_constant_address, &mut words)?;
+        let words: [u32; 3] =
+            std::array::from_fn(|i| u32::from_le_bytes(words[i * 4..][..4].try_into().unwrap()));
+        assert_ne!(words[0], 0);
+
+        let (case_preserving_detected, outline_detected) = detect_fname_layout(version, words);
+        case_preserving = options.case_preserving.unwrap_or(case_preserving_detected);
+        outline_number = outline_number.or(outline_detected);
     }
+    let outline_number = match outline_number {
+        Some(outline_number) => outline_number,
+        None if version < (4, 23) => false,
+        // confirm against the name pool when the constant did not tell
+        None => containers::fname_pool_has_numbered_entries(&mem, fnamepool, case_preserving)
+            .context(
+                "failed to determine whether FName numbers are outlined, pass --outline-number",
+            )?,
+    };
 
     let struct_info = if let Some(provided_info) = struct_info {
         provided_info
     } else {
-        structs::get_struct_info_for_version(&results.engine_version, case_preserving)
+        structs::get_struct_info_for_version(version, case_preserving, outline_number)
             .with_context(|| {
-                format!(
-                    "Failed to compute struct offsets via Gospel for {:?}",
-                    results.engine_version
-                )
+                format!("Failed to compute struct offsets via Gospel for {version:?}")
             })?
     };
 
-    Ok(Ctx::new(mem::CtxInner {
-        mem: Box::new(mem),
+    let resolved = Resolved {
         fnamepool,
-        structs: struct_info
-            .0
-            .into_iter()
-            .map(|s| (s.name.clone(), s))
-            .collect(),
+        uobjectarray,
         version,
         case_preserving,
-        uobjectarray: results.guobject_array.0,
+        outline_number,
         image_base_address: image.base_address,
-        build_change_list: results.opt.build.as_ref().ok().map(|cl| cl.0.clone()),
-    }))
+        build_change_list: build.map(|cl| cl.0),
+        sparse_delegate_storage: options
+            .sparse_delegate_storage
+            .or(sparse_delegates.map(|s| s.0 as u64)),
+    };
+    Ok(connect_resolved(mem, resolved, struct_info))
+}
+
+/// Infer (case preserving, outlined numbers) from the first three u32 words at an
+/// unnumbered static FName. Outlined numbers are `None` if the words cannot tell.
+fn detect_fname_layout(version: (u16, u16), words: [u32; 3]) -> (bool, Option<bool>) {
+    // Field offsets mirror the FName layout in jmap_dumper/unreal/src/unreal.gs:
+    //         UE <  4.23: [CMP, NUM]
+    // 4.23 <= UE <  5.01: [CMP, DISP?, NUM?]  (DISP only when case-preserving)
+    //         UE >= 5.01: [CMP, NUM?, DISP?]
+    // NUM is absent when numbers are outlined (UE_FNAME_OUTLINE_NUMBER). Words past the
+    // FName belong to whatever follows it in memory: the constant is unnumbered, so a
+    // non-zero word where NUM would be proves it absent, but a zero word may be padding.
+    let [comparison_index, at_4, at_8] = words;
+    if version < (4, 23) {
+        (false, Some(false))
+    } else if version < (5, 1) {
+        if at_4 == comparison_index {
+            (true, (at_8 != 0).then_some(true))
+        } else {
+            (false, (at_4 != 0).then_some(true))
+        }
+    } else if at_4 == comparison_index {
+        // [CMP, DISP]
+        (true, Some(true))
+    } else if at_4 == 0 {
+        if at_8 == comparison_index {
+            // [CMP, NUM, DISP]
+            (true, Some(false))
+        } else {
+            (false, None)
+        }
+    } else {
+        // [CMP]
+        (false, Some(true))
+    }
+}
+
+/// Everything about the target needed to read reflection data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resolved {
+    pub fnamepool: u64,
+    pub uobjectarray: u64,
+    pub version: (u16, u16),
+    pub case_preserving: bool,
+    #[serde(default)]
+    pub outline_number: bool,
+    pub image_base_address: u64,
+    pub build_change_list: Option<String>,
+    pub sparse_delegate_storage: Option<u64>,
+}
+
+/// Create a context from already resolved addresses, skipping image analysis
+pub fn connect_resolved(mem: impl mem::Mem + 'static, resolved: Resolved, structs: Structs) -> Ctx {
+    Ctx::new(mem::CtxInner {
+        mem: Box::new(mem),
+        fnamepool: resolved.fnamepool,
+        structs: structs.0.into_iter().map(|s| (s.name.clone(), s)).collect(),
+        version: resolved.version,
+        case_preserving: resolved.case_preserving,
+        outline_number: resolved.outline_number,
+        uobjectarray: resolved.uobjectarray,
+        image_base_address: resolved.image_base_address,
+        build_change_list: resolved.build_change_list,
+        sparse_delegate_storage: resolved.sparse_delegate_storage,
+        sparse_delegates: Default::default(),
+        fname_cache: Default::default(),
+        path_cache: Default::default(),
+        cache_scopes: Default::default(),
+        warnings: Default::default(),
+    })
 }
 
 /// Insert an object into the map, handling path collisions.
@@ -391,42 +576,95 @@ fn has_canonical_cdo(class_path: &str, obj: &ObjectType) -> bool {
     c.class_default_object.as_deref() == Some(expected.as_str())
 }
 
-fn dump_inner(
-    mem: impl mem::Mem + 'static,
-    image: &Image<'_>,
-    struct_info: Option<Structs>,
-    source_name: &str,
-    options: DumpOptions,
-) -> Result<Jmap> {
-    let mem = connect(mem, image, struct_info)?;
+/// Errors of individual properties. When lenient they are collected so the rest of the struct
+/// or object can still be read, otherwise the first one fails the whole object.
+#[derive(Default)]
+pub struct PropertyErrors {
+    lenient: bool,
+    errors: Vec<(String, anyhow::Error)>,
+}
+impl PropertyErrors {
+    pub fn new(lenient: bool) -> Self {
+        Self {
+            lenient,
+            errors: vec![],
+        }
+    }
+    fn skip(&mut self, property: &str, err: anyhow::Error) -> Result<()> {
+        if !self.lenient {
+            return Err(err.context(format!("failed to read property {property}")));
+        }
+        self.errors.push((property.to_string(), err));
+        Ok(())
+    }
+}
 
+fn dump_inner(mem: Ctx, source_name: &str, options: DumpOptions) -> Result<Jmap> {
     let uobjectarray = Ptr::<FUObjectArray>::new(mem.uobjectarray, mem.clone())?;
 
     let mut objects = BTreeMap::<String, ObjectType>::default();
     let mut child_map = HashMap::<String, BTreeSet<String>>::default();
 
-    for i in 0..uobjectarray.num_elements()? {
-        let object = uobjectarray.read_item_ptr(i as usize)?;
-        let Some(obj) = object else {
-            continue;
+    let mut diagnostics = vec![];
+    let mut diagnose =
+        |index: Option<usize>, path: Option<&str>, property: Option<&str>, err: anyhow::Error| {
+            if !options.lenient {
+                return Err(err);
+            }
+            eprintln!(
+                "{}: {}{} Failed to read: {err:#}",
+                index.map(|i| i.to_string()).unwrap_or_default(),
+                path.unwrap_or_default(),
+                property
+                    .map(|p| format!(" property {p}"))
+                    .unwrap_or_default(),
+            );
+            diagnostics.push(Diagnostic {
+                index,
+                path: path.map(|p| p.to_string()),
+                property: property.map(|p| p.to_string()),
+                error: err.chain().map(|e| e.to_string()).collect(),
+            });
+            Ok(())
         };
 
-        let path = obj.path()?;
-
-        let obj = read_object_type(obj, &path, &options);
-        // let obj = match obj {
-        //     Err(err) => {
-        //         eprintln!("{i}: {path} Failed to read: {err}");
-        //         continue;
-        //     }
-        //     r => r,
-        // };
-
-        let Some(object) = obj? else {
-            continue;
+    // the storage resolver is best effort so sparse delegate values are skipped without it
+    let sparse_delegates = match mem.sparse_delegate_storage {
+        Some(storage) => {
+            match Ptr::new(storage, mem.clone()).and_then(|s| SparseDelegates::read(&s)) {
+                Ok(sparse_delegates) => Some(Arc::new(sparse_delegates)),
+                Err(err) => {
+                    mem.warn(format!(
+                        "failed to read sparse delegate storage, sparse delegate bindings are \
+                         omitted: {err:#}"
+                    ));
+                    None
+                }
+            }
+        }
+        None => {
+            mem.warn(
+                "FSparseDelegateStorage::SparseDelegates not found, sparse delegate bindings are \
+                 omitted (pass --sparse-delegate-storage)",
+            );
+            None
+        }
+    };
+    *mem.sparse_delegates.write().unwrap() = sparse_delegates;
+
+    let extracted = extract_objects(&uobjectarray, &options)?;
+    for (i, result) in extracted {
+        let (path, object, errors) = match result {
+            Ok(Some(object)) => object,
+            Ok(None) => continue,
+            Err((path, err)) => {
+                diagnose(Some(i), path.as_deref(), None, err)?;
+                continue;
+            }
         };
-
-        // println!("{i} {path}");
+        for (property, err) in errors {
+            diagnose(Some(i), Some(&path), Some(&property), err)?;
+        }
 
         // update child_map
         if let Some(outer) = object.get_object().outer.clone() {
@@ -453,12 +691,35 @@ fn dump_inner(
     let vtables = vtable::analyze_vtables(&mem, &mut objects);
 
     let names = if options.names {
-        Some(extract_fnames(&mem)?)
+        match extract_fnames(&mem) {
+            Ok(names) => Some(names),
+            Err(err) => {
+                diagnose(None, None, None, err.context("failed to extract FNames"))?;
+                None
+            }
+        }
     } else {
         None
     };
 
+    for warning in mem.take_warnings() {
+        eprintln!("Warning: {warning}");
+        if options.lenient {
+            diagnostics.push(Diagnostic {
+                index: None,
+                path: None,
+                property: None,
+                error: vec![warning],
+            });
+        }
+    }
+
+    if let Some(path) = &options.write_snapshot {
+        snapshot::write_snapshot(&mem, source_name, path)?;
+    }
+
     Ok(Jmap {
+        format_version: jmap::FORMAT_VERSION,
         metadata: Some(Metadata {
             tool: "https://github.com/trumank/jmap".to_string(),
             timestamp: time::OffsetDateTime::now_utc().to_string(),
@@ -470,16 +731,100 @@ fn dump_inner(
             build_change_list: mem.build_change_list.clone(),
         }),
         image_base_address: mem.image_base_address.into(),
+        layout_unknown: false,
         objects,
         vtables,
         names,
+        diagnostics,
     })
 }
 
+/// Object with the errors of any properties skipped while reading it, or the error which
+/// prevented reading it at all with its path if known
+type ExtractResult = std::result::Result<
+    Option<(String, ObjectType, Vec<(String, anyhow::Error)>)>,
+    (Option<String>, anyhow::Error),
+>;
+
+fn extract_object(
+    uobjectarray: &Ptr<FUObjectArray>,
+    index: usize,
+    options: &DumpOptions,
+) -> ExtractResult {
+    let Some(obj) = uobjectarray
+        .read_item_ptr(index)
+        .map_err(|err| (None, err))?
+    else {
+        return Ok(None);
+    };
+    let path = obj.path().map_err(|err| (None, err))?;
+    let mut errors = PropertyErrors::new(options.lenient);
+    match read_object_type(obj, &path, options, &mut errors) {
+        Ok(object) => Ok(object.map(|object| (path, object, errors.errors))),
+        Err(err) => Err((Some(path), err)),
+    }
+}
+
+/// Read all objects from GUObjectArray on worker threads sharing the same context.
+/// Results are returned in object index order.
+fn extract_objects(
+    uobjectarray: &Ptr<FUObjectArray>,
+    options: &DumpOptions,
+) -> Result<Vec<(usize, ExtractResult)>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // objects are handed out in batches as their cost varies wildly by type
+    const BATCH_SIZE: usize = 256;
+
+    // paths and names are assumed stable for the duration of one pass
+    let _cache = uobjectarray.ctx().cache_scope();
+
+    let num = uobjectarray.num_elements()? as usize;
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let next = AtomicUsize::new(0);
+
+    let mut results = std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let start = next.fetch_add(BATCH_SIZE, Ordering::Relaxed);
+                        if start >= num {
+                            break;
+                        }
+                        for i in start..(start + BATCH_SIZE).min(num) {
+                            let result = extract_object(uobjectarray, i, options);
+                            let failed = result.is_err();
+                            results.push((i, result));
+                            if failed && !options.lenient {
+                                // no point continuing, the first error aborts the dump
+                                next.store(num, Ordering::Relaxed);
+                                return results;
+                            }
+                        }
+                    }
+                    results
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("worker thread panicked"))
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    Ok(results)
+}
+
 pub fn read_object_type(
     obj: Ptr<UObject>,
     path: &str,
     options: &DumpOptions,
+    errors: &mut PropertyErrors,
 ) -> Result<Option<ObjectType>> {
     let class = obj.class_private().read()?;
 
@@ -492,26 +837,26 @@ pub fn read_object_type(
 
     let f = class.class_cast_flags().read()?;
     let object = if !is_basic_object && f.contains(EClassCastFlags::CASTCLASS_UClass) {
-        ObjectType::Class(read_class(&obj.cast())?)
+        ObjectType::Class(read_class(&obj.cast(), errors)?)
     } else if !is_basic_object && f.contains(EClassCastFlags::CASTCLASS_UFunction) {
         let full_obj = obj.cast::<UFunction>();
         let function_flags = full_obj.function_flags().read()?;
         ObjectType::Function(Function {
-            r#struct: read_struct(&obj.cast())?,
+            r#struct: read_struct(&obj.cast(), errors)?,
             function_flags,
             func: (full_obj.func().read()? as u64).into(),
         })
     } else if !is_basic_object && f.contains(EClassCastFlags::CASTCLASS_UScriptStruct) {
-        ObjectType::ScriptStruct(read_script_struct(&obj.cast())?)
+        ObjectType::ScriptStruct(read_script_struct(&obj.cast(), errors)?)
     } else if !is_basic_object && f.contains(EClassCastFlags::CASTCLASS_UEnum) {
-        ObjectType::Enum(read_enum(&obj.cast())?)
+        ObjectType::Enum(read_enum(&obj.cast(), errors)?)
     } else if !is_basic_object && f.contains(EClassCastFlags::CASTCLASS_UPackage) {
         ObjectType::Package(Package {
-            object: read_object(&obj)?,
+            object: read_object(&obj, errors)?,
         })
     } else {
         let obj = obj.cast::<UObject>();
-        ObjectType::Object(read_object(&obj)?)
+        ObjectType::Object(read_object(&obj, errors)?)
         //println!("{path:?} {:?}", f);
     };
     Ok(Some(object))
@@ -657,8 +1002,13 @@ pub fn read_prop_type(ptr: &Ptr<ZProperty>) -> Result<Property> {
         let interface_class = prop.interface_class().read()?.path()?;
         PropertyType::Interface { interface_class }
     } else if f.contains(EClassCastFlags::CASTCLASS_FFieldPathProperty) {
-        // TODO
-        PropertyType::FieldPath
+        let prop = ptr.cast::<ZFieldPathProperty>();
+        let property_class = prop
+            .property_class()
+            .read()?
+            .map(|c| c.name().read())
+            .transpose()?;
+        PropertyType::FieldPath { property_class }
     } else if f.contains(EClassCastFlags::CASTCLASS_FOptionalProperty) {
         let prop = ptr.cast::<FOptionalProperty>();
         PropertyType::Optional {
@@ -669,7 +1019,7 @@ pub fn read_prop_type(ptr: &Ptr<ZProperty>) -> Result<Property> {
     } else if f.contains(EClassCastFlags::CASTCLASS_FAnsiStrProperty) {
         PropertyType::AnsiStr
     } else {
-        unimplemented!("{f:?}");
+        bail!("unknown property cast flags {f:?}");
     };
 
     let prop = ptr.cast::<ZProperty>();
@@ -687,40 +1037,53 @@ pub fn read_prop_type(ptr: &Ptr<ZProperty>) -> Result<Property> {
 pub fn read_props(
     ustruct: &Ptr<UStruct>,
     ptr: &Ptr<()>,
+) -> Result<OrderMap<String, PropertyValue>> {
+    read_props_with(ustruct, ptr, &mut PropertyErrors::default())
+}
+
+fn read_props_with(
+    ustruct: &Ptr<UStruct>,
+    ptr: &Ptr<()>,
+    errors: &mut PropertyErrors,
 ) -> Result<OrderMap<String, PropertyValue>> {
     let mut properties = OrderMap::new();
     for prop in ustruct.properties(true) {
         let prop = prop?;
-        let array_dim = prop.array_dim().read()? as usize;
         let name = prop.zfield().name_private().read()?;
-        if array_dim == 1 {
-            if let Some(value) = read_prop(&prop, ptr, 0)? {
+        match read_prop_elements(&prop, ptr) {
+            Ok(Some(value)) => {
                 properties.insert(name, value);
             }
-        } else {
-            let mut elements = vec![];
-            let mut success = true;
-            for i in 0..array_dim {
-                if let Some(value) = read_prop(&prop, ptr, i)? {
-                    elements.push(value);
-                } else {
-                    success = false;
-                }
-            }
-            if success {
-                properties.insert(name, PropertyValue::Array(elements));
-            }
+            Ok(None) => {}
+            Err(err) => errors.skip(&name, err)?,
         }
     }
     Ok(properties)
 }
 
+/// Value of a property, static arrays are read as an `Array` of their elements
+fn read_prop_elements(prop: &Ptr<ZProperty>, ptr: &Ptr<()>) -> Result<Option<PropertyValue>> {
+    let array_dim = prop.array_dim().read()? as usize;
+    if array_dim == 1 {
+        return read_prop(prop, ptr, 0);
+    }
+    let mut elements = vec![];
+    for i in 0..array_dim {
+        let Some(value) = read_prop(prop, ptr, i)? else {
+            return Ok(None);
+        };
+        elements.push(value);
+    }
+    Ok(Some(PropertyValue::Array(elements)))
+}
+
 pub fn read_prop(
     prop: &Ptr<ZProperty>,
     ptr: &Ptr<()>,
     index: usize,
 ) -> Result<Option<PropertyValue>> {
     let size = prop.element_size().read()? as usize;
+    let container = ptr;
     let ptr = ptr.byte_offset(prop.offset_internal().read()? as usize + index * size);
     let f = prop.zfield().cast_flags()?;
 
@@ -732,15 +1095,34 @@ pub fn read_prop(
     } else if f.contains(EClassCastFlags::CASTCLASS_FNameProperty) {
         PropertyValue::Name(ptr.cast::<FName>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FTextProperty) {
-        return Ok(None);
+        PropertyValue::Text(ptr.cast::<FText>().read()?.into())
     } else if f.contains(EClassCastFlags::CASTCLASS_FMulticastInlineDelegateProperty) {
-        return Ok(None);
+        PropertyValue::MulticastInlineDelegate(ptr.cast::<FMulticastScriptDelegate>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FMulticastSparseDelegateProperty) {
-        return Ok(None);
+        // missing storage is reported once per dump
+        let Some(sparse_delegates) = ptr.ctx().sparse_delegates.read().unwrap().clone() else {
+            return Ok(None);
+        };
+        let bindings = if ptr.cast::<FSparseDelegate>().is_bound()? {
+            // sparse delegates are only valid as UObject members so the container is the owner
+            let name = prop.zfield().name_private().read()?;
+            let Some(bindings) = sparse_delegates.get(container.address(), &name) else {
+                // most likely the storage was resolved to the wrong address
+                ptr.ctx().warn(
+                    "bound sparse delegates missing from FSparseDelegateStorage, their bindings \
+                     are omitted",
+                );
+                return Ok(None);
+            };
+            bindings.to_vec()
+        } else {
+            vec![]
+        };
+        PropertyValue::MulticastSparseDelegate(bindings)
     } else if f.contains(EClassCastFlags::CASTCLASS_FMulticastDelegateProperty) {
-        return Ok(None);
+        PropertyValue::MulticastDelegate(ptr.cast::<FMulticastScriptDelegate>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FDelegateProperty) {
-        return Ok(None);
+        PropertyValue::Delegate(ptr.cast::<FScriptDelegate>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FBoolProperty) {
         let prop = prop.cast::<ZBoolProperty>();
         let byte_offset = prop.byte_offset_().read()?;
@@ -768,8 +1150,8 @@ pub fn read_prop(
         PropertyValue::Array(data)
     } else if f.contains(EClassCastFlags::CASTCLASS_FEnumProperty) {
         let prop = prop.cast::<ZEnumProperty>();
-        let underlying =
-            read_prop(&prop.underlying_prop().read()?, &ptr, 0)?.expect("valid underlying prop");
+        let underlying = read_prop(&prop.underlying_prop().read()?, &ptr, 0)?
+            .context("unreadable underlying enum prop")?;
         let value = match underlying {
             PropertyValue::Byte(BytePropertyValue::Value(v)) => v as i64,
             PropertyValue::Int8(v) => v as i64,
@@ -781,7 +1163,7 @@ pub fn read_prop(
             PropertyValue::UInt64(v) => v as i64,
             e => bail!("underlying enum prop {e:?}"),
         };
-        let names = read_enum(&prop.enum_().read()?.expect("valid enum"))?.names;
+        let names = prop.enum_().read()?.context("null enum")?.read_names()?;
         let name = names
             .into_iter()
             .find_map(|(name, v)| (v == value).then_some(name));
@@ -854,10 +1236,10 @@ pub fn read_prop(
             if let Some(name) = prop
                 .enum_()
                 .read()?
-                .map(|e| read_enum(&e))
+                .map(|e| e.read_names())
                 .transpose()?
-                .and_then(|e| {
-                    e.names
+                .and_then(|names| {
+                    names
                         .into_iter()
                         .find_map(|(name, v)| (v == value as i64).then_some(name))
                 })
@@ -889,29 +1271,41 @@ pub fn read_prop(
             .transpose()?;
         PropertyValue::Object(obj)
     } else if f.contains(EClassCastFlags::CASTCLASS_FWeakObjectProperty) {
-        return Ok(None);
+        PropertyValue::WeakObject(ptr.cast::<FWeakObjectPtr>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FSoftObjectProperty) {
-        return Ok(None);
+        PropertyValue::SoftObject(ptr.cast::<FSoftObjectPtr>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FLazyObjectProperty) {
-        return Ok(None);
+        PropertyValue::LazyObject(ptr.cast::<FLazyObjectPtr>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FInterfaceProperty) {
-        return Ok(None);
+        PropertyValue::Interface(ptr.cast::<FScriptInterface>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FFieldPathProperty) {
-        return Ok(None);
+        PropertyValue::FieldPath(ptr.cast::<FFieldPath>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FOptionalProperty) {
-        return Ok(None);
+        let prop = prop.cast::<FOptionalProperty>();
+        let Some(is_set) = prop.is_set(&ptr)? else {
+            return Ok(None);
+        };
+        let value = if is_set {
+            let Some(value) = read_prop(&prop.value_property().read()?, &ptr, 0)? else {
+                return Ok(None);
+            };
+            Some(value.into())
+        } else {
+            None
+        };
+        PropertyValue::Optional(value)
     } else if f.contains(EClassCastFlags::CASTCLASS_FUtf8StrProperty) {
         PropertyValue::Utf8Str(ptr.cast::<FUtf8String>().read()?)
     } else if f.contains(EClassCastFlags::CASTCLASS_FAnsiStrProperty) {
         // technically needs to be C locale but probably never going to encounter non-ASCII characters anyway
         PropertyValue::Utf8Str(ptr.cast::<FUtf8String>().read()?)
     } else {
-        unimplemented!("{f:?}");
+        bail!("unknown property cast flags {f:?}");
     };
     Ok(Some(value))
 }
 
-pub fn read_object(obj: &Ptr<UObject>) -> Result<Object> {
+pub fn read_object(obj: &Ptr<UObject>, errors: &mut PropertyErrors) -> Result<Object> {
     let outer = obj.outer_private().read()?.map(|s| s.path()).transpose()?;
 
     let class = obj.class_private().read()?;
@@ -924,23 +1318,26 @@ pub fn read_object(obj: &Ptr<UObject>) -> Result<Object> {
         outer,
         class: class_name,
         children: Default::default(),
-        property_values: read_props(&class.ustruct(), &obj.cast())?.into(),
+        property_values: read_props_with(&class.ustruct(), &obj.cast(), errors)?.into(),
     })
 }
 
-pub fn read_struct(obj: &Ptr<UStruct>) -> Result<Struct> {
+pub fn read_struct(obj: &Ptr<UStruct>, errors: &mut PropertyErrors) -> Result<Struct> {
     let mut properties = vec![];
     for prop in obj.properties(false) {
         let prop = prop?;
         let f = prop.zfield().cast_flags()?;
         if f.contains(EClassCastFlags::CASTCLASS_FProperty) {
-            properties.push(read_prop_type(&prop.cast::<ZProperty>())?);
+            match read_prop_type(&prop.cast::<ZProperty>()) {
+                Ok(property) => properties.push(property),
+                Err(err) => errors.skip(&prop.zfield().name_private().read()?, err)?,
+            }
         }
     }
 
     let super_struct = obj.super_struct().read()?.map(|s| s.path()).transpose()?;
     Ok(Struct {
-        object: read_object(&obj.cast())?,
+        object: read_object(&obj.cast(), errors)?,
         super_struct,
         properties,
         properties_size: obj.properties_size().read()? as usize,
@@ -949,14 +1346,17 @@ pub fn read_struct(obj: &Ptr<UStruct>) -> Result<Struct> {
     })
 }
 
-pub fn read_script_struct(obj: &Ptr<UScriptStruct>) -> Result<ScriptStruct> {
+pub fn read_script_struct(
+    obj: &Ptr<UScriptStruct>,
+    errors: &mut PropertyErrors,
+) -> Result<ScriptStruct> {
     Ok(ScriptStruct {
-        r#struct: read_struct(&obj.ustruct())?,
+        r#struct: read_struct(&obj.ustruct(), errors)?,
         struct_flags: obj.struct_flags().read()?,
     })
 }
 
-pub fn read_class(obj: &Ptr<UClass>) -> Result<Class> {
+pub fn read_class(obj: &Ptr<UClass>, errors: &mut PropertyErrors) -> Result<Class> {
     let class_flags = obj.class_flags().read()?;
     let class_cast_flags = obj.class_cast_flags().read()?;
     let class_default_object = obj
@@ -965,7 +1365,7 @@ pub fn read_class(obj: &Ptr<UClass>) -> Result<Class> {
         .map(|s| s.path())
         .transpose()?;
     Ok(Class {
-        r#struct: read_struct(&obj.cast())?,
+        r#struct: read_struct(&obj.cast(), errors)?,
         class_flags,
         class_cast_flags,
         class_default_object,
@@ -973,9 +1373,9 @@ pub fn read_class(obj: &Ptr<UClass>) -> Result<Class> {
     })
 }
 
-pub fn read_enum(obj: &Ptr<UEnum>) -> Result<Enum> {
+pub fn read_enum(obj: &Ptr<UEnum>, errors: &mut PropertyErrors) -> Result<Enum> {
     Ok(Enum {
-        object: read_object(&obj.cast())?,
+        object: read_object(&obj.cast(), errors)?,
         cpp_type: obj.cpp_type().read()?,
         cpp_form: obj.cpp_form().read()?,
         enum_flags: (obj.ctx().ue_version() >= (4, 26))
@@ -984,3 +1384,28 @@ pub fn read_enum(obj: &Ptr<UEnum>) -> Result<Enum> {
         names: obj.read_names()?,
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect_fname_layout() {
+        // trailing zeros may be NUM or unrelated padding
+        assert_eq!(detect_fname_layout((4, 27), [5, 0, 0]), (false, None));
+        assert_eq!(detect_fname_layout((4, 27), [5, 5, 0]), (true, None));
+        assert_eq!(detect_fname_layout((5, 3), [5, 0, 7]), (false, None));
+        // a non-zero word where NUM would be
+        assert_eq!(detect_fname_layout((4, 27), [5, 7, 0]), (false, Some(true)));
+        assert_eq!(detect_fname_layout((4, 27), [5, 5, 7]), (true, Some(true)));
+        assert_eq!(detect_fname_layout((5, 3), [5, 7, 0]), (false, Some(true)));
+        // DISP found at the offset only one layout puts it
+        assert_eq!(detect_fname_layout((5, 3), [5, 5, 0]), (true, Some(true)));
+        assert_eq!(detect_fname_layout((5, 3), [5, 0, 5]), (true, Some(false)));
+        // no DISP or outlined numbers before 4.23
+        assert_eq!(
+            detect_fname_layout((4, 22), [5, 7, 7]),
+            (false, Some(false))
+        );
+    }
+}
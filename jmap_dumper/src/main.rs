@@ -1,14 +1,20 @@
 use anyhow::{Result, bail};
-use clap::{ArgGroup, Parser};
-use jmap::Jmap;
-use jmap_dumper::{DumpOptions, Input, into_header, structs::Structs};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use jmap::{Jmap, index::ObjectPath};
+use jmap_dumper::{ConnectOptions, DumpOptions, Input, into_header, structs::Structs};
 use std::io::Cursor;
-use std::{collections::BTreeMap, fs::File, io::BufWriter, path::PathBuf};
+use std::io::Write;
+use std::{collections::BTreeMap, fs::File, io::BufWriter, path::Path, path::PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None,
-        group = ArgGroup::new("input").args(&["pid", "minidump", "jmap"]).required(true))]
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true,
+        group = ArgGroup::new("input").args(&["pid", "minidump", "core", "snapshot", "jmap"]).required(true))]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Dump from process ID
     #[arg(long, short, group = "input")]
     pid: Option<i32>,
@@ -17,7 +23,15 @@ struct Cli {
     #[arg(long, short, group = "input")]
     minidump: Option<PathBuf>,
 
-    /// Use existing .jmap dump
+    /// Dump from ELF core file (gcore, systemd-coredump)
+    #[arg(long, group = "input")]
+    core: Option<PathBuf>,
+
+    /// Replay a snapshot written with --write-snapshot
+    #[arg(long, group = "input")]
+    snapshot: Option<PathBuf>,
+
+    /// Use existing .jmap, .jmap.gz or .jmapb dump
     #[arg(long, short, group = "input")]
     jmap: Option<PathBuf>,
 
@@ -33,27 +47,232 @@ struct Cli {
     #[arg(long)]
     names: bool,
 
+    /// Skip objects which fail to read and record them as diagnostics instead of aborting
+    #[arg(long)]
+    lenient: bool,
+
+    /// Number of threads used to read objects (defaults to all cores)
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+
+    /// Override engine version, e.g. 5.3
+    #[arg(long, value_parser = parse_version)]
+    engine_version: Option<(u16, u16)>,
+
+    /// Override FNamePool address (hex)
+    #[arg(long, value_parser = parse_address)]
+    fname_pool: Option<u64>,
+
+    /// Override GUObjectArray address (hex)
+    #[arg(long, value_parser = parse_address)]
+    guobject_array: Option<u64>,
+
+    /// Override whether the build uses case preserving names
+    #[arg(long)]
+    case_preserving: Option<bool>,
+
+    /// Override whether FName numbers are outlined into separate name entries
+    #[arg(long)]
+    outline_number: Option<bool>,
+
+    /// Override address (hex) of a static FName constant used to detect the FName layout
+    #[arg(long, value_parser = parse_address)]
+    fname_constant: Option<u64>,
+
+    /// Override FSparseDelegateStorage::SparseDelegates address (hex)
+    #[arg(long, value_parser = parse_address)]
+    sparse_delegate_storage: Option<u64>,
+
+    /// Write all memory read during the dump to a compact snapshot for offline replay
+    #[arg(long)]
+    write_snapshot: Option<PathBuf>,
+
+    /// Extensions to include when writing .usmap output
+    #[arg(long, value_enum, value_delimiter = ',')]
+    usmap_ext: Vec<UsmapExt>,
+
     /// Output dump .jmap path
-    #[arg(index = 1)]
-    output: PathBuf,
+    #[arg(index = 1, required = true)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two .jmap dumps
+    Diff {
+        /// Old .jmap, .jmap.gz or .jmapb
+        old: PathBuf,
+        /// New .jmap, .jmap.gz or .jmapb
+        new: PathBuf,
+        /// Output a JSON Patch (RFC 6902) from the old jmap JSON to the new one instead of text
+        #[arg(long)]
+        json: bool,
+        /// Output file (stdout if not specified)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Check a .jmap for dangling references and broken struct layouts
+    Check {
+        /// .jmap, .jmap.gz or .jmapb to check
+        jmap: PathBuf,
+        /// Output the issues as JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write the JSON Schema of the .jmap format
+    Schema {
+        /// Output file (stdout if not specified)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Validate a .jmap against the JSON Schema of the current format version, upgrading older
+    /// versions first
+    Validate {
+        /// .jmap or .jmap.gz to validate
+        jmap: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum UsmapExt {
+    /// Package paths of enums and structs
+    Ppth,
+    /// Enum, struct, class and property flags
+    Eatr,
+    /// Full enum names and values
+    Envp,
+}
+
+fn parse_address(s: &str) -> Result<u64, std::num::ParseIntError> {
+    u64::from_str_radix(s.trim_start_matches("0x"), 16)
+}
+
+fn parse_version(s: &str) -> Result<(u16, u16)> {
+    let (major, minor) = s
+        .split_once('.')
+        .ok_or_else(|| anyhow::anyhow!("expected <major>.<minor>"))?;
+    Ok((major.parse()?, minor.parse()?))
+}
+
+fn read_jmap(path: &Path) -> Result<Jmap> {
+    let filename = path.file_name().unwrap().to_str().unwrap();
+    if filename.ends_with(".jmapb") {
+        jmap::binary::JmapBinary::new(std::fs::read(path)?)?.to_jmap()
+    } else {
+        jmap::migrate::from_value(read_json(path)?)
+    }
+}
+
+/// Unparsed JSON of a .jmap or .jmap.gz
+fn read_json(path: &Path) -> Result<serde_json::Value> {
+    let filename = path.file_name().unwrap().to_str().unwrap();
+    Ok(if filename.ends_with(".jmap.gz") {
+        let compressed = std::fs::read(path)?;
+        let decoder = flate2::read::GzDecoder::new(Cursor::new(&compressed));
+        serde_json::from_reader(decoder)?
+    } else if filename.ends_with(".jmap") {
+        serde_json::from_slice(&std::fs::read(path)?)?
+    } else {
+        bail!("Error: Expected .jmap, .jmap.gz or .jmapb file as input");
+    })
+}
+
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::Diff {
+            old,
+            new,
+            json,
+            output,
+        } => {
+            let (old, new) = (read_jmap(&old)?, read_jmap(&new)?);
+            let mut out: Box<dyn Write> = match output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            if json {
+                let patch = jmap::diff::json_patch(
+                    &serde_json::to_value(&old)?,
+                    &serde_json::to_value(&new)?,
+                );
+                serde_json::to_writer_pretty(&mut out, &patch)?;
+                writeln!(out)?;
+            } else {
+                write!(out, "{}", jmap::diff::diff(&old, &new))?;
+            }
+            out.flush()?;
+        }
+        Command::Check { jmap, json } => {
+            let issues = read_jmap(&jmap)?.validate();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&issues)?);
+            } else {
+                for issue in &issues {
+                    println!("{issue}");
+                }
+            }
+            if !issues.is_empty() {
+                bail!("{} issues found", issues.len());
+            }
+            if !json {
+                println!("No issues found");
+            }
+        }
+        Command::Schema { output } => {
+            let mut out: Box<dyn Write> = match output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            serde_json::to_writer_pretty(&mut out, &jmap::schema::schema())?;
+            writeln!(out)?;
+            out.flush()?;
+        }
+        Command::Validate { jmap } => {
+            let mut value = read_json(&jmap)?;
+            let version = jmap::migrate::format_version(&value)?;
+            jmap::migrate::migrate(&mut value)?;
+            if version != jmap::FORMAT_VERSION {
+                println!(
+                    "Upgraded from format version {version} to {}",
+                    jmap::FORMAT_VERSION
+                );
+            }
+            let errors = jmap::schema::validate(&value)?;
+            for error in &errors {
+                println!("{error}");
+            }
+            if !errors.is_empty() {
+                bail!("{} schema violations found", errors.len());
+            }
+            println!("Valid");
+        }
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(command) = cli.command {
+        return run_command(command);
+    }
+    let output = cli.output.expect("output is required without a subcommand");
+
     enum OutputType {
         Jmap,
         JmapGz,
+        JmapBinary,
         Usmap,
         Header,
     }
 
-    let output_type = match cli.output.file_name().and_then(|e| e.to_str()) {
+    let output_type = match output.file_name().and_then(|e| e.to_str()) {
         Some(n) if n.ends_with(".jmap") => OutputType::Jmap,
         Some(n) if n.ends_with(".jmap.gz") => OutputType::JmapGz,
+        Some(n) if n.ends_with(".jmapb") => OutputType::JmapBinary,
         Some(n) if n.ends_with(".usmap") => OutputType::Usmap,
         Some(n) if n.ends_with(".h") || n.ends_with(".hpp") => OutputType::Header,
-        _ => bail!("Error: Expected .jmap, .jmap.gz, .usmap, or .hpp output type"),
+        _ => bail!("Error: Expected .jmap, .jmap.gz, .jmapb, .usmap, or .hpp output type"),
     };
 
     let struct_info: Option<Structs> = if let Some(path) = cli.struct_info {
@@ -65,70 +284,96 @@ fn main() -> Result<()> {
     let options = DumpOptions {
         all: cli.all,
         names: cli.names,
+        lenient: cli.lenient,
+        threads: cli.threads,
+        connect: ConnectOptions {
+            engine_version: cli.engine_version,
+            fname_pool: cli.fname_pool,
+            guobject_array: cli.guobject_array,
+            case_preserving: cli.case_preserving,
+            outline_number: cli.outline_number,
+            fname_constant: cli.fname_constant,
+            sparse_delegate_storage: cli.sparse_delegate_storage,
+        },
+        write_snapshot: cli.write_snapshot,
     };
 
     let reflection_data: Jmap = if let Some(path) = cli.jmap {
-        let filename = path.file_name().unwrap().to_str().unwrap();
-        if filename.ends_with(".jmap.gz") {
-            let compressed = std::fs::read(path)?;
-            let decoder = flate2::read::GzDecoder::new(Cursor::new(&compressed));
-            serde_json::from_reader(decoder)?
-        } else if filename.ends_with(".jmap") {
-            serde_json::from_slice(&std::fs::read(path)?)?
-        } else {
-            bail!("Error: Expected .jmap or .jmap.gz file as input");
-        }
+        read_jmap(&path)?
     } else if let Some(pid) = cli.pid {
         jmap_dumper::dump(Input::Process(pid), struct_info, options)?
     } else if let Some(path) = cli.minidump {
         jmap_dumper::dump(Input::Dump(path), struct_info, options)?
+    } else if let Some(path) = cli.core {
+        jmap_dumper::dump(Input::CoreFile(path), struct_info, options)?
+    } else if let Some(path) = cli.snapshot {
+        jmap_dumper::dump(Input::Snapshot(path), struct_info, options)?
     } else {
         unreachable!();
     };
 
     match output_type {
         OutputType::Jmap => {
-            let mut file = BufWriter::new(File::create(&cli.output)?);
+            let mut file = BufWriter::new(File::create(&output)?);
             serde_json::to_writer_pretty(&mut file, &reflection_data)?;
         }
         OutputType::JmapGz => {
-            let mut file = BufWriter::new(File::create(&cli.output)?);
+            let mut file = BufWriter::new(File::create(&output)?);
             let mut e = flate2::write::GzEncoder::new(&mut file, flate2::Compression::default());
             serde_json::to_writer_pretty(&mut e, &reflection_data)?;
             e.finish()?;
         }
+        OutputType::JmapBinary => {
+            let mut file = BufWriter::new(File::create(&output)?);
+            jmap::binary::write_binary(&reflection_data, &mut file)?;
+            file.flush()?;
+        }
         OutputType::Usmap => {
-            let usmap = into_usmap(&reflection_data);
+            let usmap = into_usmap(&reflection_data, &cli.usmap_ext);
             usmap.write(&mut std::io::BufWriter::new(std::fs::File::create(
-                &cli.output,
+                &output,
             )?))?;
         }
         OutputType::Header => {
             let header = into_header(&reflection_data);
-            std::fs::write(&cli.output, header)?;
+            std::fs::write(&output, header)?;
         }
     }
 
-    println!("Success! Output written to {}", cli.output.display());
+    println!("Success! Output written to {}", output.display());
 
     Ok(())
 }
 
 fn obj_name(path: &str) -> &str {
-    path.rsplit(['/', '.', ':']).next().unwrap()
+    ObjectPath::parse(path).name()
 }
 
-fn into_usmap(reflection_data: &Jmap) -> usmap::Usmap {
+fn into_usmap(reflection_data: &Jmap, extensions: &[UsmapExt]) -> usmap::Usmap {
     let mut enums = vec![];
     let mut structs = vec![];
 
+    let mut enum_paths = vec![];
+    let mut struct_paths = vec![];
+    let mut enum_flags = vec![];
+    let mut struct_flags = vec![];
+    let mut value_pairs = vec![];
+
     for (path, obj) in &reflection_data.objects {
         let struct_ = match &obj {
-            jmap::ObjectType::ScriptStruct(obj) => Some(&obj.r#struct),
-            jmap::ObjectType::Class(obj) => Some(&obj.r#struct),
+            jmap::ObjectType::ScriptStruct(obj) => Some((
+                &obj.r#struct,
+                usmap::FlagsType::Struct,
+                obj.struct_flags.bits(),
+            )),
+            jmap::ObjectType::Class(obj) => Some((
+                &obj.r#struct,
+                usmap::FlagsType::Class,
+                obj.class_flags.bits(),
+            )),
             _ => None,
         };
-        if let Some(s) = struct_ {
+        if let Some((s, flags_type, flags)) = struct_ {
             let mut properties = vec![];
             let mut index = 0;
             for prop in &s.properties {
@@ -140,6 +385,12 @@ fn into_usmap(reflection_data: &Jmap) -> usmap::Usmap {
                 super_struct: s.super_struct.as_ref().map(|s| obj_name(s).to_string()),
                 properties,
             });
+            struct_paths.push(package_path(path).to_string());
+            struct_flags.push(usmap::StructFlags {
+                type_: flags_type,
+                value: flags,
+                prop_flags: s.properties.iter().map(|p| p.flags.bits()).collect(),
+            });
         } else if let Some(e) = obj.get_enum() {
             let prefix = format!("{}::", obj_name(path));
             let mut entries = BTreeMap::new();
@@ -156,19 +407,60 @@ fn into_usmap(reflection_data: &Jmap) -> usmap::Usmap {
                 name: obj_name(path).to_string(),
                 entries,
             });
+            enum_paths.push(package_path(path).to_string());
+            enum_flags.push(e.enum_flags.map_or(0, |f| f.bits() as u32));
+            value_pairs.push(
+                e.names
+                    .iter()
+                    .map(|(name, value)| (name.clone(), *value as u64))
+                    .collect(),
+            );
         }
     }
 
+    let ppth = extensions
+        .contains(&UsmapExt::Ppth)
+        .then(|| usmap::ExtPpth {
+            version: 0,
+            enums: enum_paths,
+            structs: struct_paths,
+        });
+    let eatr = extensions
+        .contains(&UsmapExt::Eatr)
+        .then(|| usmap::ExtEatr {
+            version: 0,
+            enum_flags,
+            struct_flags,
+        });
+    let envp = extensions
+        .contains(&UsmapExt::Envp)
+        .then(|| usmap::ExtEnvp {
+            version: 0,
+            value_pairs,
+        });
+    let num_ext = [ppth.is_some(), eatr.is_some(), envp.is_some()]
+        .into_iter()
+        .filter(|e| *e)
+        .count() as u32;
+
     usmap::Usmap {
         enums,
         structs,
-        cext: None,
-        eatr: None,
-        envp: None,
-        ppth: None,
+        cext: (num_ext > 0).then_some(usmap::ExtCext {
+            version: 0,
+            num_ext,
+        }),
+        eatr,
+        envp,
+        ppth,
     }
 }
 
+/// Path of the outermost package containing the object
+fn package_path(path: &str) -> &str {
+    path.split(['.', ':']).next().unwrap()
+}
+
 fn into_usmap_prop(index: usize, prop: &jmap::Property) -> usmap::Property {
     usmap::Property {
         name: prop.name.clone(),
@@ -188,7 +480,7 @@ fn into_usmap_prop_inner(prop: &jmap::PropertyType) -> usmap::PropertyInner {
         PT::Str => PI::Str,
         PT::Name => PI::Name,
         PT::Text => PI::Text,
-        // TODO distinguish between sparse/inline?
+        // usmap has a single multicast delegate type
         PT::MulticastInlineDelegate { .. } => PI::MulticastDelegate,
         PT::MulticastSparseDelegate { .. } => PI::MulticastDelegate,
         PT::MulticastDelegate { .. } => PI::MulticastDelegate,
@@ -247,7 +539,7 @@ fn into_usmap_prop_inner(prop: &jmap::PropertyType) -> usmap::PropertyInner {
         PT::SoftClass { .. } => PI::SoftObject,
         PT::LazyObject { property_class: _ } => PI::LazyObject,
         PT::Interface { interface_class: _ } => PI::Interface,
-        PT::FieldPath => PI::FieldPath,
+        PT::FieldPath { .. } => PI::FieldPath,
         PT::Optional { inner } => PI::Optional {
             inner: into_usmap_prop_inner(&inner.r#type).into(),
         },
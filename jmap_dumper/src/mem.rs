@@ -1,3 +1,4 @@
+use crate::delegates::SparseDelegates;
 use crate::structs::StructInfo;
 use anyhow::{Context as _, Result};
 use jmap::{
@@ -5,10 +6,13 @@ use jmap::{
     EPropertyFlags, EStructFlags,
 };
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     marker::PhantomData,
     num::NonZero,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
 
 // --- Pod trait (merged TryFromBytes + Pod) ---
@@ -72,35 +76,42 @@ pub trait Mem: Send + Sync {
         anyhow::bail!("write not supported for this memory backend")
     }
     fn clear_cache(&self) {}
+    /// Pages read so far, if this backend keeps them
+    fn cached_pages(&self) -> BTreeMap<u64, Vec<u8>> {
+        Default::default()
+    }
 }
 
 // --- MemCache ---
 
-const PAGE_SIZE: usize = 0x1000;
+pub const PAGE_SIZE: usize = 0x1000;
+/// Number of independently locked page maps so concurrent readers rarely contend
+const SHARDS: usize = 64;
 pub struct MemCache<M> {
     inner: M,
-    pages: Mutex<HashMap<u64, Vec<u8>>>,
+    shards: Vec<RwLock<HashMap<u64, Vec<u8>>>>,
 }
 impl<M: Mem> MemCache<M> {
     pub fn wrap(inner: M) -> Self {
         Self {
             inner,
-            pages: Default::default(),
+            shards: (0..SHARDS).map(|_| Default::default()).collect(),
         }
     }
+    fn shard(&self, page_start: u64) -> &RwLock<HashMap<u64, Vec<u8>>> {
+        &self.shards[(page_start / PAGE_SIZE as u64) as usize % SHARDS]
+    }
 }
 impl<M: Mem> Mem for MemCache<M> {
     fn write_buf(&self, address: u64, buf: &[u8]) -> Result<()> {
         // Invalidate any cached pages that overlap the write
-        let mut lock = self.pages.lock().unwrap();
         let start_page = address & !(PAGE_SIZE as u64 - 1);
         let end_page = (address + buf.len() as u64).saturating_sub(1) & !(PAGE_SIZE as u64 - 1);
         let mut page = start_page;
         while page <= end_page {
-            lock.remove(&page);
+            self.shard(page).write().unwrap().remove(&page);
             page += PAGE_SIZE as u64;
         }
-        drop(lock);
         self.inner.write_buf(address, buf)
     }
 
@@ -108,8 +119,6 @@ impl<M: Mem> Mem for MemCache<M> {
         let mut remaining = buf.len();
         let mut cur = 0;
 
-        let mut lock = self.pages.lock().unwrap();
-
         while remaining > 0 {
             let page_start = (address + cur as u64) & !(PAGE_SIZE as u64 - 1);
             let page_offset = address as usize + cur - page_start as usize;
@@ -117,13 +126,15 @@ impl<M: Mem> Mem for MemCache<M> {
 
             let buf_region = &mut buf[cur..cur + to_copy];
             let page_range = page_offset..page_offset + to_copy;
-            if let Some(page) = lock.get(&page_start) {
+            let shard = self.shard(page_start);
+            if let Some(page) = shard.read().unwrap().get(&page_start) {
                 buf_region.copy_from_slice(&page[page_range]);
             } else {
+                // read without holding the lock, racing readers of the same page are harmless
                 let mut page = vec![0; PAGE_SIZE];
                 self.inner.read_buf(page_start, &mut page)?;
                 buf_region.copy_from_slice(&page[page_range]);
-                lock.insert(page_start, page);
+                shard.write().unwrap().insert(page_start, page);
             }
 
             remaining -= to_copy;
@@ -134,7 +145,22 @@ impl<M: Mem> Mem for MemCache<M> {
     }
 
     fn clear_cache(&self) {
-        self.pages.lock().unwrap().clear();
+        for shard in &self.shards {
+            shard.write().unwrap().clear();
+        }
+    }
+
+    fn cached_pages(&self) -> BTreeMap<u64, Vec<u8>> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                let shard = shard.read().unwrap();
+                shard
+                    .iter()
+                    .map(|(address, page)| (*address, page.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
     }
 }
 
@@ -231,9 +257,34 @@ pub struct CtxInner {
     pub structs: HashMap<String, StructInfo>,
     pub version: (u16, u16),
     pub case_preserving: bool,
+    /// FName numbers are stored in separate name entries (UE_FNAME_OUTLINE_NUMBER)
+    pub outline_number: bool,
     pub uobjectarray: u64,
     pub image_base_address: u64,
     pub build_change_list: Option<String>,
+    /// Address of `FSparseDelegateStorage::SparseDelegates` if known
+    pub sparse_delegate_storage: Option<u64>,
+    /// Contents of the sparse delegate storage, read at the start of each dump
+    pub sparse_delegates: RwLock<Option<Arc<SparseDelegates>>>,
+    /// Memoized FName entries by comparison index, only used within a [`CacheScope`]
+    pub fname_cache: RwLock<HashMap<u32, String>>,
+    /// Memoized object paths by object address, only used within a [`CacheScope`]
+    pub path_cache: RwLock<HashMap<u64, String>>,
+    /// Number of live [`CacheScope`]s
+    pub cache_scopes: AtomicUsize,
+    /// Problems which lost part of a value without failing to read it, see [`Ctx::warn`]
+    pub warnings: Mutex<BTreeSet<String>>,
+}
+
+/// Enables the FName and path caches of a [`Ctx`], clearing them once the last scope is dropped
+pub struct CacheScope(Ctx);
+impl Drop for CacheScope {
+    fn drop(&mut self) {
+        if self.0.cache_scopes.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.fname_cache.write().unwrap().clear();
+            self.0.path_cache.write().unwrap().clear();
+        }
+    }
 }
 
 /// Shared context: single Arc clone per Ptr operation. Deref to `CtxInner` for field access.
@@ -276,6 +327,18 @@ impl Ctx {
     }
     pub fn clear_cache(&self) {
         self.mem.clear_cache();
+        self.fname_cache.write().unwrap().clear();
+        self.path_cache.write().unwrap().clear();
+    }
+    /// Memoize FNames and object paths until the returned scope is dropped. Both are keyed by
+    /// address so this is only valid while the target does not free or rename objects, e.g.
+    /// for a single pass over GUObjectArray.
+    pub fn cache_scope(&self) -> CacheScope {
+        self.cache_scopes.fetch_add(1, Ordering::SeqCst);
+        CacheScope(self.clone())
+    }
+    pub fn caching(&self) -> bool {
+        self.cache_scopes.load(Ordering::SeqCst) > 0
     }
 
     pub fn get_struct(&self, struct_name: &str) -> &StructInfo {
@@ -298,6 +361,13 @@ impl Ctx {
     pub fn ue_version(&self) -> (u16, u16) {
         self.version
     }
+    /// Record a warning to report at the end of the dump, duplicates are reported once
+    pub fn warn(&self, warning: impl Into<String>) {
+        self.warnings.lock().unwrap().insert(warning.into());
+    }
+    pub fn take_warnings(&self) -> BTreeSet<String> {
+        std::mem::take(&mut self.warnings.lock().unwrap())
+    }
 }
 
 impl std::ops::Deref for Ctx {
@@ -0,0 +1,213 @@
+use anyhow::Result;
+
+use crate::containers::{FName, FString, TArray};
+use crate::mem::Ptr;
+use crate::objects::{FUObjectArray, UObject};
+
+#[derive(Debug, Clone, Copy)]
+pub struct FWeakObjectPtr;
+impl Ptr<FWeakObjectPtr> {
+    pub fn object_index(&self) -> Ptr<i32> {
+        let offset = self.ctx().struct_member("FWeakObjectPtr", "ObjectIndex");
+        self.byte_offset(offset).cast()
+    }
+    pub fn object_serial_number(&self) -> Ptr<i32> {
+        let offset = self
+            .ctx()
+            .struct_member("FWeakObjectPtr", "ObjectSerialNumber");
+        self.byte_offset(offset).cast()
+    }
+    /// Resolve through GUObjectArray. Returns None if null or stale
+    pub fn get(&self) -> Result<Option<Ptr<UObject>>> {
+        let index = self.object_index().read()?;
+        let serial_number = self.object_serial_number().read()?;
+        if index < 0 || serial_number == 0 {
+            return Ok(None);
+        }
+        let uobjectarray = Ptr::<FUObjectArray>::new(self.ctx().uobjectarray, self.ctx().clone())?;
+        uobjectarray.read_weak(index as usize, serial_number)
+    }
+    pub fn read(&self) -> Result<Option<String>> {
+        self.get()?.map(|obj| obj.path()).transpose()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FSoftObjectPath;
+impl Ptr<FSoftObjectPath> {
+    pub fn asset_path(&self) -> Ptr<FTopLevelAssetPath> {
+        let offset = self.ctx().struct_member("FSoftObjectPath", "AssetPath");
+        self.byte_offset(offset).cast()
+    }
+    pub fn asset_path_name(&self) -> Ptr<FName> {
+        let offset = self.ctx().struct_member("FSoftObjectPath", "AssetPathName");
+        self.byte_offset(offset).cast()
+    }
+    pub fn sub_path_string(&self) -> Ptr<FString> {
+        let offset = self.ctx().struct_member("FSoftObjectPath", "SubPathString");
+        self.byte_offset(offset).cast()
+    }
+    /// Format as `/Package/Path.AssetName:SubPath`, None if the path is null
+    pub fn read(&self) -> Result<Option<String>> {
+        let mut path = if self.ctx().ue_version() >= (5, 1) {
+            let asset_path = self.asset_path();
+            let package_name = asset_path.package_name().read()?;
+            if package_name == "None" {
+                return Ok(None);
+            }
+            let asset_name = asset_path.asset_name().read()?;
+            if asset_name == "None" {
+                package_name
+            } else {
+                format!("{package_name}.{asset_name}")
+            }
+        } else {
+            let asset_path_name = self.asset_path_name().read()?;
+            if asset_path_name == "None" {
+                return Ok(None);
+            }
+            asset_path_name
+        };
+        let sub_path = self.sub_path_string().read()?;
+        if !sub_path.is_empty() {
+            path.push(':');
+            path.push_str(&sub_path);
+        }
+        Ok(Some(path))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FTopLevelAssetPath;
+impl Ptr<FTopLevelAssetPath> {
+    pub fn package_name(&self) -> Ptr<FName> {
+        let offset = self
+            .ctx()
+            .struct_member("FTopLevelAssetPath", "PackageName");
+        self.byte_offset(offset).cast()
+    }
+    pub fn asset_name(&self) -> Ptr<FName> {
+        let offset = self.ctx().struct_member("FTopLevelAssetPath", "AssetName");
+        self.byte_offset(offset).cast()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FSoftObjectPtr;
+impl Ptr<FSoftObjectPtr> {
+    pub fn object_id(&self) -> Ptr<FSoftObjectPath> {
+        let offset = self.ctx().struct_member("FSoftObjectPtr", "ObjectID");
+        self.byte_offset(offset).cast()
+    }
+    pub fn read(&self) -> Result<Option<String>> {
+        self.object_id().read()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FLazyObjectPtr;
+impl Ptr<FLazyObjectPtr> {
+    /// FUniqueObjectGuid as 4 consecutive u32 components
+    pub fn object_id(&self) -> Ptr<u32> {
+        let offset = self.ctx().struct_member("FLazyObjectPtr", "ObjectID");
+        self.byte_offset(offset).cast()
+    }
+    /// Format the object GUID the same way as `FGuid::ToString()`, None if the GUID is invalid
+    pub fn read(&self) -> Result<Option<String>> {
+        let guid = self.object_id().read_vec(4)?;
+        if guid.iter().all(|c| *c == 0) {
+            return Ok(None);
+        }
+        Ok(Some(format!(
+            "{:08X}{:08X}{:08X}{:08X}",
+            guid[0], guid[1], guid[2], guid[3]
+        )))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FFieldPath;
+impl Ptr<FFieldPath> {
+    pub fn resolved_owner(&self) -> Ptr<FWeakObjectPtr> {
+        let offset = self.ctx().struct_member("FFieldPath", "ResolvedOwner");
+        self.byte_offset(offset).cast()
+    }
+    /// Names from the innermost field out to the package of the owning UStruct
+    pub fn path(&self) -> Ptr<TArray<FName>> {
+        let offset = self.ctx().struct_member("FFieldPath", "Path");
+        self.byte_offset(offset).cast()
+    }
+    /// Format as `/Script/Pkg.Class:PropName`, None if the path is empty
+    pub fn read(&self) -> Result<Option<String>> {
+        let path = self.path();
+        let num = path.len()?;
+        let Some(data) = path.data()? else {
+            return Ok(None);
+        };
+        if num == 0 {
+            return Ok(None);
+        }
+        let name_size = self.ctx().get_struct("FName").size as usize;
+        let mut names = Vec::with_capacity(num);
+        for i in 0..num {
+            names.push(data.byte_offset(i * name_size).read()?);
+        }
+        Ok(Some(field_path_string(&names)))
+    }
+}
+
+/// Same format as `FFieldPath::ToString`: outermost package first, `.` before the top level
+/// object and `:` before everything nested in it
+fn field_path_string(path: &[String]) -> String {
+    let mut result = String::new();
+    for (i, name) in path.iter().enumerate().rev() {
+        if !result.is_empty() {
+            result.push(if i + 2 < path.len() { ':' } else { '.' });
+        }
+        result.push_str(name);
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FScriptInterface;
+impl Ptr<FScriptInterface> {
+    pub fn object_pointer(&self) -> Ptr<Option<Ptr<UObject>>> {
+        let offset = self
+            .ctx()
+            .struct_member("FScriptInterface", "ObjectPointer");
+        self.byte_offset(offset).cast()
+    }
+    pub fn read(&self) -> Result<Option<String>> {
+        self.object_pointer()
+            .read()?
+            .map(|obj| obj.path())
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_field_path_string() {
+        assert_eq!(
+            field_path_string(&path(&["Speed", "Vehicle", "/Script/Game"])),
+            "/Script/Game.Vehicle:Speed"
+        );
+        assert_eq!(
+            field_path_string(&path(&["Value", "Inner", "Vehicle", "/Script/Game"])),
+            "/Script/Game.Vehicle:Inner:Value"
+        );
+        assert_eq!(
+            field_path_string(&path(&["Vehicle", "/Script/Game"])),
+            "/Script/Game.Vehicle"
+        );
+        assert_eq!(field_path_string(&path(&["/Script/Game"])), "/Script/Game");
+    }
+}
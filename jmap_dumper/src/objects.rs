@@ -1,5 +1,5 @@
 use crate::{
-    containers::{FName, FString, TArray},
+    containers::{FName, FScriptArray, FString, TArray},
     mem::Ptr,
     read_path,
 };
@@ -428,6 +428,10 @@ impl Ptr<ZField> {
 #[derive(Clone, Copy)]
 pub struct FFieldClass;
 impl Ptr<FFieldClass> {
+    pub fn name(&self) -> Ptr<FName> {
+        let offset = self.ctx().struct_member("FFieldClass", "Name");
+        self.byte_offset(offset).cast()
+    }
     pub fn cast_flags(&self) -> Ptr<EClassCastFlags> {
         let offset = self.ctx().struct_member("FFieldClass", "CastFlags");
         self.byte_offset(offset).cast()
@@ -549,6 +553,16 @@ impl Ptr<ZInterfaceProperty> {
     }
 }
 #[derive(Clone, Copy)]
+pub struct ZFieldPathProperty;
+impl Ptr<ZFieldPathProperty> {
+    pub fn property_class(&self) -> Ptr<Option<Ptr<FFieldClass>>> {
+        let offset = self
+            .ctx()
+            .struct_member("ZFieldPathProperty", "PropertyClass");
+        self.byte_offset(offset).cast()
+    }
+}
+#[derive(Clone, Copy)]
 pub struct ZArrayProperty;
 impl Ptr<ZArrayProperty> {
     pub fn inner(&self) -> Ptr<Ptr<ZProperty>> {
@@ -645,6 +659,32 @@ impl Ptr<FOptionalProperty> {
             .struct_member("FOptionalPropertyLayout", "ValueProperty");
         self.byte_offset(parent + offset).cast()
     }
+    /// Whether the optional at `data` holds a value. Returns None if the value
+    /// type uses an intrusive unset state that is not understood.
+    pub fn is_set(&self, data: &Ptr<()>) -> Result<Option<bool>> {
+        let value_prop = self.value_property().read()?;
+        let value_size =
+            value_prop.element_size().read()? as usize * value_prop.array_dim().read()? as usize;
+        let size = self.cast::<ZProperty>().element_size().read()? as usize;
+
+        // without an intrusive unset state the value is followed by a bool
+        if size > value_size {
+            return Ok(Some(data.byte_offset(value_size).cast::<u8>().read()? != 0));
+        }
+
+        let f = value_prop.zfield().cast_flags()?;
+        if f.intersects(
+            EClassCastFlags::CASTCLASS_FStrProperty
+                | EClassCastFlags::CASTCLASS_FArrayProperty
+                | EClassCastFlags::CASTCLASS_FUtf8StrProperty
+                | EClassCastFlags::CASTCLASS_FAnsiStrProperty,
+        ) {
+            // TArray(FIntrusiveUnsetOptionalState) marks itself with ArrayMax = -1
+            let array = data.cast::<FScriptArray>();
+            return Ok(Some(array.max().read()? >= 0));
+        }
+        Ok(None)
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -675,6 +715,10 @@ impl Ptr<FUObjectItem> {
         let offset = self.ctx().struct_member("FUObjectItem", "Object");
         self.byte_offset(offset).cast()
     }
+    pub fn serial_number(&self) -> Ptr<i32> {
+        let offset = self.ctx().struct_member("FUObjectItem", "SerialNumber");
+        self.byte_offset(offset).cast()
+    }
     /// Offset by n items using the runtime struct size
     pub fn offset_item(&self, n: usize) -> Self {
         let stride = self.ctx().get_struct("FUObjectItem").size as usize;
@@ -794,6 +838,29 @@ impl Ptr<FUObjectArray> {
                 .read()
         }
     }
+    /// Resolve a weak object reference. Serial numbers are only validated for UE 4.11+
+    /// where they are stored in FUObjectItem
+    pub fn read_weak(&self, index: usize, serial_number: i32) -> Result<Option<Ptr<UObject>>> {
+        if index >= self.num_elements()? as usize {
+            return Ok(None);
+        }
+        if self.ctx().ue_version() < (4, 11) {
+            return self.read_item_ptr(index);
+        }
+        let item = if self.ctx().ue_version() < (4, 20) {
+            self.obj_objects()
+                .cast::<FFixedUObjectArray>()
+                .read_item_ptr(index)?
+        } else {
+            self.obj_objects()
+                .cast::<FChunkedFixedUObjectArray>()
+                .read_item_ptr(index)?
+        };
+        if item.serial_number().read()? != serial_number {
+            return Ok(None);
+        }
+        item.object().read()
+    }
     pub fn num_elements(&self) -> Result<i32> {
         if self.ctx().ue_version() < (4, 8) {
             self.obj_objects()
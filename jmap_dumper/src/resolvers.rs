@@ -0,0 +1,59 @@
+//! Resolvers for globals not covered by patternsleuth's built in resolvers
+
+use iced_x86::{Decoder, DecoderOptions, Mnemonic, Register};
+use patternsleuth::MemoryTrait;
+use patternsleuth::image::Image;
+use patternsleuth::resolvers::{ensure_one, impl_resolver_singleton, unreal::util};
+use patternsleuth::scanner::Pattern;
+
+/// `FSparseDelegateStorage::SparseDelegates`, the map holding the bindings of every bound
+/// sparse delegate keyed by owning object
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SparseDelegateStorage(pub usize);
+impl_resolver_singleton!(all, SparseDelegateStorage, |ctx| async {
+    // FSparseDelegateStorage::SparseDelegateReport parses "delegate=" from its arguments
+    // before locking SparseDelegateMapCritical and iterating SparseDelegates
+    let strings = ctx
+        .scan(Pattern::from_bytes(util::utf16("delegate=")).unwrap())
+        .await;
+    let refs = util::scan_xrefs(ctx, &strings).await;
+    let fns = util::root_functions(ctx, &refs)?;
+
+    let mut found = vec![];
+    for f in fns {
+        found.extend(map_after_lock(ctx.image(), f)?);
+    }
+    Ok(Self(ensure_one(found)?))
+});
+
+/// First global referenced after the critical section passed to the first call in `function`
+fn map_after_lock(
+    image: &Image<'_>,
+    function: usize,
+) -> Result<Option<usize>, patternsleuth::MemoryAccessError> {
+    let Some(range) = image.get_root_function_range(function)? else {
+        return Ok(None);
+    };
+    let bytes = image.memory.range(range.clone())?;
+    let mut decoder = Decoder::with_ip(64, bytes, range.start as u64, DecoderOptions::NONE);
+
+    let mut lea_rcx = None;
+    let mut lock = None;
+    for inst in &mut decoder {
+        match lock {
+            None if inst.is_call_near() => lock = lea_rcx,
+            None => {
+                lea_rcx = (inst.mnemonic() == Mnemonic::Lea
+                    && inst.op0_register() == Register::RCX
+                    && inst.is_ip_rel_memory_operand())
+                .then(|| inst.ip_rel_memory_address());
+            }
+            Some(lock) => {
+                if inst.is_ip_rel_memory_operand() && inst.ip_rel_memory_address() != lock {
+                    return Ok(Some(inst.ip_rel_memory_address() as usize));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,238 @@
+//! Compact memory snapshots containing only the pages touched during a dump.
+//!
+//! Layout (gzip compressed):
+//! - magic `JMAPSNAP`, u32 format version
+//! - u32 header length followed by the JSON encoded [`SnapshotHeader`]
+//! - u64 page count followed by `(u64 address, [u8; PAGE_SIZE])` pages
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::Resolved;
+use crate::mem::{Ctx, Mem, PAGE_SIZE};
+use crate::structs::Structs;
+
+const MAGIC: &[u8; 8] = b"JMAPSNAP";
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    /// Name of the original data source
+    pub source: String,
+    pub resolved: Resolved,
+    pub structs: Structs,
+}
+
+/// Write the resolved addresses, struct layouts and all cached pages of `ctx`
+pub fn write_snapshot(ctx: &Ctx, source: &str, path: impl AsRef<Path>) -> Result<()> {
+    let pages = ctx.mem.cached_pages();
+    if pages.is_empty() {
+        bail!("memory backend does not record pages, nothing to snapshot");
+    }
+
+    let header = SnapshotHeader {
+        source: source.to_string(),
+        resolved: Resolved {
+            fnamepool: ctx.fnamepool,
+            uobjectarray: ctx.uobjectarray,
+            version: ctx.version,
+            case_preserving: ctx.case_preserving,
+            outline_number: ctx.outline_number,
+            image_base_address: ctx.image_base_address,
+            build_change_list: ctx.build_change_list.clone(),
+            sparse_delegate_storage: ctx.sparse_delegate_storage,
+        },
+        structs: Structs(ctx.structs.values().cloned().collect()),
+    };
+    let header = serde_json::to_vec(&header)?;
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut w = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    w.write_all(MAGIC)?;
+    w.write_all(&VERSION.to_le_bytes())?;
+    w.write_all(&(header.len() as u32).to_le_bytes())?;
+    w.write_all(&header)?;
+    w.write_all(&(pages.len() as u64).to_le_bytes())?;
+    for (address, page) in pages {
+        w.write_all(&address.to_le_bytes())?;
+        w.write_all(&page)?;
+    }
+    w.finish()?.flush()?;
+    Ok(())
+}
+
+pub fn read_snapshot(path: impl AsRef<Path>) -> Result<(SnapshotHeader, SnapshotMem)> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut r = flate2::read::GzDecoder::new(file);
+
+    let mut magic = [0; 8];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        bail!("not a jmap snapshot");
+    }
+    let version = read_u32(&mut r)?;
+    if version != VERSION {
+        bail!("unsupported snapshot version {version}");
+    }
+
+    let mut header = vec![0; read_u32(&mut r)? as usize];
+    r.read_exact(&mut header)?;
+    let header: SnapshotHeader = serde_json::from_slice(&header)?;
+
+    let mut pages = HashMap::new();
+    for _ in 0..read_u64(&mut r)? {
+        let address = read_u64(&mut r)?;
+        let mut page = vec![0; PAGE_SIZE];
+        r.read_exact(&mut page)?;
+        pages.insert(address, page);
+    }
+
+    Ok((header, SnapshotMem { pages }))
+}
+
+/// Open a snapshot for replay, returning the context and original source name.
+/// Provided `struct_info` overrides the layouts stored in the snapshot.
+pub fn connect_snapshot(
+    path: impl AsRef<Path>,
+    struct_info: Option<Structs>,
+) -> Result<(Ctx, String)> {
+    let (header, mem) = read_snapshot(path)?;
+    let structs = struct_info.unwrap_or(header.structs);
+    let ctx = crate::connect_resolved(mem, header.resolved, structs);
+    Ok((ctx, header.source))
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+fn read_u64(r: &mut impl Read) -> Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Replays memory from a snapshot. Reads outside of the recorded pages fail.
+pub struct SnapshotMem {
+    pages: HashMap<u64, Vec<u8>>,
+}
+
+impl Mem for SnapshotMem {
+    fn read_buf(&self, address: u64, buf: &mut [u8]) -> Result<()> {
+        let mut cur = 0;
+        while cur < buf.len() {
+            let page_start = (address + cur as u64) & !(PAGE_SIZE as u64 - 1);
+            let page_offset = address as usize + cur - page_start as usize;
+            let to_copy = (buf.len() - cur).min(PAGE_SIZE - page_offset);
+
+            let Some(page) = self.pages.get(&page_start) else {
+                bail!(
+                    "address 0x{:x} not present in snapshot",
+                    address + cur as u64
+                );
+            };
+            buf[cur..cur + to_copy].copy_from_slice(&page[page_offset..page_offset + to_copy]);
+            cur += to_copy;
+        }
+        Ok(())
+    }
+
+    fn cached_pages(&self) -> std::collections::BTreeMap<u64, Vec<u8>> {
+        self.pages.clone().into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::mem::MemCache;
+    use crate::{MemoryRegion, RegionMem};
+
+    const BASE: u64 = 0x10000;
+
+    fn resolved() -> Resolved {
+        Resolved {
+            fnamepool: BASE,
+            uobjectarray: BASE + 0x100,
+            version: (5, 3),
+            case_preserving: false,
+            outline_number: false,
+            image_base_address: 0x140000000,
+            build_change_list: Some("++UE5+Release-5.3-CL-29314046".into()),
+            sparse_delegate_storage: None,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{name}-{}.jmapsnap", std::process::id()))
+    }
+
+    /// Snapshot two pages of memory of which only the first is read
+    fn write_test_snapshot(path: &Path) -> Result<&'static [u8]> {
+        let data: &'static [u8] = (0..2 * PAGE_SIZE)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>()
+            .leak();
+        let regions = vec![MemoryRegion::new(BASE, data)];
+        let mem = MemCache::wrap(RegionMem::from_regions(regions));
+        let ctx = crate::connect_resolved(mem, resolved(), Structs(vec![]));
+        ctx.read_buf(BASE + 0x100, &mut [0; 16])?;
+        write_snapshot(&ctx, "test.dmp", path)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() -> Result<()> {
+        let path = temp_path("round_trip");
+        let data = write_test_snapshot(&path)?;
+        let (header, mem) = read_snapshot(&path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(header.source, "test.dmp");
+        assert_eq!(header.resolved.fnamepool, BASE);
+        assert_eq!(header.resolved.version, (5, 3));
+        assert_eq!(
+            header.resolved.build_change_list,
+            resolved().build_change_list
+        );
+
+        let mut buf = [0; 32];
+        mem.read_buf(BASE + 0x200, &mut buf)?;
+        assert_eq!(buf, data[0x200..0x220]);
+        // the second page was never read so it is not part of the snapshot
+        assert!(mem.read_buf(BASE + PAGE_SIZE as u64, &mut buf).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_version_mismatch() -> Result<()> {
+        let path = temp_path("version_mismatch");
+        write_test_snapshot(&path)?;
+
+        let mut raw = vec![];
+        flate2::read::GzDecoder::new(std::fs::File::open(&path)?).read_to_end(&mut raw)?;
+        raw[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        let mut w = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path)?,
+            flate2::Compression::default(),
+        );
+        w.write_all(&raw)?;
+        w.finish()?;
+
+        let result = read_snapshot(&path);
+        std::fs::remove_file(&path)?;
+        let err = result.err().expect("newer snapshot version is rejected");
+        assert_eq!(
+            err.to_string(),
+            format!("unsupported snapshot version {}", VERSION + 1)
+        );
+        Ok(())
+    }
+}
@@ -9,7 +9,6 @@ use gospel_typelib::target_triplet::{
 use gospel_typelib::type_model::{ResolvedUDTMemberLayout, Type, TypeGraphLike, TypeLayoutCache};
 use gospel_vm::vm::{GospelVMOptions, GospelVMRunContext, GospelVMState, GospelVMValue};
 use include_dir::{Dir, include_dir};
-use patternsleuth::resolvers::unreal::engine_version::EngineVersion;
 use serde::{Deserialize, Serialize};
 
 static UNREAL_MODULE_SRC: Dir = include_dir!("$CARGO_MANIFEST_DIR/unreal/src");
@@ -34,8 +33,9 @@ pub struct StructMember {
 }
 
 pub fn get_struct_info_for_version(
-    version: &EngineVersion,
+    version: (u16, u16),
     case_preserving: bool,
+    outline_number: bool,
 ) -> Result<Structs> {
     let target_triplet = TargetTriplet {
         arch: TargetArchitecture::X86_64,
@@ -65,7 +65,7 @@ pub fn get_struct_info_for_version(
 
     let mut vm_state = GospelVMState::create();
 
-    let ue_version = (version.major as u64) * 100 + (version.minor as u64);
+    let ue_version = (version.0 as u64) * 100 + (version.1 as u64);
 
     let struct_names: Vec<(&str, &str)> = vec![
         ("uobjectarray", "FUObjectArray"),
@@ -94,6 +94,7 @@ pub fn get_struct_info_for_version(
         ("properties", "ZMapProperty"),
         ("properties", "ZDelegateProperty"),
         ("properties", "ZMulticastDelegateProperty"),
+        ("properties", "ZFieldPathProperty"),
         ("properties", "ZObjectPropertyBase"),
         ("properties", "ZObjectProperty"),
         ("properties", "ZClassProperty"),
@@ -110,6 +111,27 @@ pub fn get_struct_info_for_version(
         ("containers", "FScriptSet"),
         ("containers", "FScriptMap"),
         ("unreal", "FName"),
+        ("unreal", "FText"),
+        ("unreal", "FTextKey"),
+        ("unreal", "FTextId"),
+        ("unreal", "FTextData"),
+        ("unreal", "FTextHistory"),
+        ("unreal", "FTextHistory_None"),
+        ("unreal", "FTextHistory_Base"),
+        ("unreal", "FTextHistory_Generated"),
+        ("unreal", "FTextDataVTable"),
+        ("unreal", "FTextHistoryVTable"),
+        ("unreal", "FWeakObjectPtr"),
+        ("unreal", "FScriptDelegate"),
+        ("unreal", "FMulticastScriptDelegate"),
+        ("unreal", "FSparseDelegateObjectElement"),
+        ("unreal", "FSparseDelegateElement"),
+        ("unreal", "FTopLevelAssetPath"),
+        ("unreal", "FSoftObjectPath"),
+        ("unreal", "FSoftObjectPtr"),
+        ("unreal", "FLazyObjectPtr"),
+        ("unreal", "FFieldPath"),
+        ("unreal", "FScriptInterface"),
         ("properties", "FField"),
         ("properties", "FFieldClass"),
     ];
@@ -120,7 +142,8 @@ pub fn get_struct_info_for_version(
     let vm_options = GospelVMOptions::default()
         .target_triplet(target_triplet)
         .with_global("UE_VERSION", ue_version)
-        .with_global("WITH_CASE_PRESERVING_NAME", case_preserving as u64);
+        .with_global("WITH_CASE_PRESERVING_NAME", case_preserving as u64)
+        .with_global("UE_FNAME_OUTLINE_NUMBER", outline_number as u64);
     let mut execution_context = GospelVMRunContext::create(vm_options);
 
     for (file_name, struct_name) in struct_names {
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use jmap::{ETextFlag, ETextHistoryType, TextPropertyValue};
+
+use crate::containers::FString;
+use crate::mem::Ptr;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FText;
+impl Ptr<FText> {
+    pub fn text_data(&self) -> Ptr<Option<Ptr<()>>> {
+        let offset = self.ctx().struct_member("FText", "TextData");
+        self.byte_offset(offset).cast()
+    }
+    pub fn flags(&self) -> Ptr<u32> {
+        let offset = self.ctx().struct_member("FText", "Flags");
+        self.byte_offset(offset).cast()
+    }
+    pub fn read(&self) -> Result<TextPropertyValue> {
+        let flags = ETextFlag::from_bits_retain(self.flags().read()?);
+
+        let mut value = TextPropertyValue {
+            history: None,
+            flags,
+            namespace: None,
+            key: None,
+            source_string: None,
+            display_string: None,
+        };
+
+        let Some(text_data) = self.text_data().read()? else {
+            return Ok(value);
+        };
+
+        // before 5.0 the history is wrapped in a TLocalizedTextData or TGeneratedTextData
+        let wrapper = (self.ctx().ue_version() < (5, 0)).then(|| text_data.cast::<FTextData>());
+        let history = match &wrapper {
+            Some(text_data) => {
+                value.display_string = read_string_ptr(&text_data.localized_string())?;
+                text_data.history()
+            }
+            None => text_data.cast::<FTextHistory>(),
+        };
+
+        let history_type = history.history_type()?;
+        value.history = history_type;
+
+        match history_type {
+            Some(ETextHistoryType::Base) => {
+                let base = history.cast::<FTextHistory_Base>();
+                if self.ctx().ue_version() >= (4, 26) {
+                    let text_id = base.text_id();
+                    value.namespace = text_id.namespace().read()?;
+                    value.key = text_id.key().read()?;
+                }
+                value.source_string = Some(base.source_string().read()?);
+                if self.ctx().ue_version() >= (5, 0) {
+                    value.display_string = read_string_ptr(&base.localized_string())?;
+                }
+            }
+            // culture invariant and generated texts are TGeneratedTextData before 5.0
+            Some(t) if t == ETextHistoryType::None || t.is_generated() => match &wrapper {
+                Some(text_data) => {
+                    if let Some(display_string) = text_data.generated_display_string()? {
+                        value.display_string = Some(display_string);
+                    }
+                }
+                None if t == ETextHistoryType::None => {
+                    let none = history.cast::<FTextHistory_None>();
+                    value.display_string = Some(none.display_string().read()?);
+                }
+                None => {
+                    let generated = history.cast::<FTextHistory_Generated>();
+                    value.display_string = Some(generated.display_string().read()?);
+                }
+            },
+            _ => {}
+        }
+
+        Ok(value)
+    }
+}
+
+fn read_string_ptr(ptr: &Ptr<Option<Ptr<FString>>>) -> Result<Option<String>> {
+    ptr.read()?.map(|s| s.read()).transpose()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FTextKey;
+impl Ptr<FTextKey> {
+    pub fn str_ptr(&self) -> Ptr<Option<Ptr<u16>>> {
+        let offset = self.ctx().struct_member("FTextKey", "StrPtr");
+        self.byte_offset(offset).cast()
+    }
+    pub fn read(&self) -> Result<Option<String>> {
+        let Some(chars) = self.str_ptr().read()? else {
+            return Ok(None);
+        };
+        let mut data = vec![];
+        for i in 0.. {
+            let next = chars.offset(i).read()?;
+            if next == 0 {
+                break;
+            }
+            data.push(next);
+        }
+        Ok(Some(String::from_utf16(&data)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FTextId;
+impl Ptr<FTextId> {
+    pub fn namespace(&self) -> Ptr<FTextKey> {
+        let offset = self.ctx().struct_member("FTextId", "Namespace");
+        self.byte_offset(offset).cast()
+    }
+    pub fn key(&self) -> Ptr<FTextKey> {
+        let offset = self.ctx().struct_member("FTextId", "Key");
+        self.byte_offset(offset).cast()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FTextData;
+impl Ptr<FTextData> {
+    pub fn vtable(&self) -> Ptr<Ptr<Ptr<u8>>> {
+        self.cast()
+    }
+    pub fn localized_string(&self) -> Ptr<Option<Ptr<FString>>> {
+        let offset = self.ctx().struct_member("FTextData", "LocalizedString");
+        self.byte_offset(offset).cast()
+    }
+    pub fn history(&self) -> Ptr<FTextHistory> {
+        let offset = self.ctx().struct_member("FTextData", "History");
+        self.byte_offset(offset).cast()
+    }
+    /// `TGeneratedTextData::DisplayString`, None if this is not a TGeneratedTextData. It
+    /// follows the history so its offset depends on the concrete history type, which is why
+    /// it is taken from `GetDisplayString`, a plain `return DisplayString;` in this class.
+    pub fn generated_display_string(&self) -> Result<Option<String>> {
+        let slot = self
+            .ctx()
+            .struct_member("FTextDataVTable", "GetDisplayString");
+        let func = self.vtable().read()?.byte_offset(slot).read()?;
+        let Some(offset) = decode_member_return(&func)? else {
+            // TLocalizedTextData returns the LocalizedString which was read already
+            return Ok(None);
+        };
+        Ok(Some(self.byte_offset(offset).cast::<FString>().read()?))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FTextHistory;
+impl Ptr<FTextHistory> {
+    pub fn vtable(&self) -> Ptr<Ptr<Ptr<u8>>> {
+        self.cast()
+    }
+    /// Determine history type by decoding the body of the virtual `FTextHistory::GetType`
+    /// which is always a trivial `return ETextHistoryType::X;`
+    pub fn history_type(&self) -> Result<Option<ETextHistoryType>> {
+        let slot = self.ctx().struct_member("FTextHistoryVTable", "GetType");
+        let func = self.vtable().read()?.byte_offset(slot).read()?;
+        let history_type =
+            decode_const_return(&func)?.and_then(|value| ETextHistoryType::from_repr(value as i8));
+        if history_type.is_none() {
+            self.ctx().warn(format!(
+                "failed to decode FTextHistory::GetType at 0x{:x}, history types of texts using \
+                 it are omitted",
+                func.address()
+            ));
+        }
+        Ok(history_type)
+    }
+}
+
+/// Start of the body of `func`, following an incremental linking thunk
+fn function_body(func: &Ptr<u8>, len: usize) -> Result<Vec<u8>> {
+    let mut func = func.clone();
+    if func.read()? == 0xe9 {
+        let rel = func.byte_offset(1).cast::<i32>().read()?;
+        func = func.map(|addr| addr.wrapping_add(5).wrapping_add_signed(rel as i64))?;
+    }
+    func.read_vec(len)
+}
+
+/// Decode the member offset of a function consisting of only `return this->Member;` where the
+/// member is returned by reference
+fn decode_member_return(func: &Ptr<u8>) -> Result<Option<usize>> {
+    let bytes = function_body(func, 8)?;
+    Ok(match bytes[..] {
+        // lea rax, [rcx + disp8]; ret
+        [0x48, 0x8d, 0x41, disp, 0xc3, ..] => Some(disp as usize),
+        // lea rax, [rcx + disp32]; ret
+        [0x48, 0x8d, 0x81, a, b, c, d, 0xc3] => Some(u32::from_le_bytes([a, b, c, d]) as usize),
+        _ => None,
+    })
+}
+
+/// Decode the return value of a function consisting of only `return <constant>;`
+fn decode_const_return(func: &Ptr<u8>) -> Result<Option<i32>> {
+    let bytes = function_body(func, 6)?;
+    Ok(match bytes[..] {
+        // xor eax, eax; ret
+        [0x33 | 0x31, 0xc0, 0xc3, ..] => Some(0),
+        // mov al, imm8; ret
+        [0xb0, imm, 0xc3, ..] => Some(imm as i8 as i32),
+        // mov eax, imm32; ret
+        [0xb8, a, b, c, d, 0xc3] => Some(i32::from_le_bytes([a, b, c, d])),
+        // or al, 0xff; ret (ETextHistoryType::None)
+        [0x0c, 0xff, 0xc3, ..] => Some(-1),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub struct FTextHistory_None;
+impl Ptr<FTextHistory_None> {
+    pub fn display_string(&self) -> Ptr<FString> {
+        let offset = self
+            .ctx()
+            .struct_member("FTextHistory_None", "DisplayString");
+        self.byte_offset(offset).cast()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub struct FTextHistory_Base;
+impl Ptr<FTextHistory_Base> {
+    pub fn text_id(&self) -> Ptr<FTextId> {
+        let offset = self.ctx().struct_member("FTextHistory_Base", "TextId");
+        self.byte_offset(offset).cast()
+    }
+    pub fn source_string(&self) -> Ptr<FString> {
+        let offset = self
+            .ctx()
+            .struct_member("FTextHistory_Base", "SourceString");
+        self.byte_offset(offset).cast()
+    }
+    pub fn localized_string(&self) -> Ptr<Option<Ptr<FString>>> {
+        let offset = self
+            .ctx()
+            .struct_member("FTextHistory_Base", "LocalizedString");
+        self.byte_offset(offset).cast()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub struct FTextHistory_Generated;
+impl Ptr<FTextHistory_Generated> {
+    pub fn display_string(&self) -> Ptr<FString> {
+        let offset = self
+            .ctx()
+            .struct_member("FTextHistory_Generated", "DisplayString");
+        self.byte_offset(offset).cast()
+    }
+}
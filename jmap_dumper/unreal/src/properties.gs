@@ -188,4 +188,8 @@ class ZMulticastDelegateProperty : ZProperty {
 
 class ZMulticastSparseDelegateProperty : ZMulticastDelegateProperty {};
 
+class ZFieldPathProperty : ZProperty {
+    FFieldClass* PropertyClass;
+};
+
 class ZOptionalProperty : ZProperty, FOptionalPropertyLayout {};
@@ -12,7 +12,7 @@ import unreal::properties::{
     ZInterfaceProperty, ZSoftObjectProperty, ZSoftClassProperty,
     ZWeakObjectProperty, ZLazyObjectProperty, ZStructProperty,
     ZDelegateProperty, ZMulticastDelegateProperty, ZMulticastSparseDelegateProperty,
-    ZOptionalProperty
+    ZFieldPathProperty, ZOptionalProperty
 };
 import unreal::objects::{
     EObjectFlags, EClassFlags, EClassCastFlags, EFunctionFlags, EStructFlags,
@@ -64,6 +64,11 @@ struct alignas(if (UE_VERSION >= 411 && UE_VERSION < 422) 8 else 4) FName {
         // UE 4.22: Transition version, no union, int32_t ComparisonIndex
         int32_t ComparisonIndex;
         uint32_t Number;
+    } else if (UE_FNAME_OUTLINE_NUMBER) {
+        // UE 4.23+ with UE_FNAME_OUTLINE_NUMBER: the number lives in a separate numbered
+        // FNameEntry referenced by ComparisonIndex (and DisplayIndex)
+        FNameEntryId ComparisonIndex;
+        if (WITH_CASE_PRESERVING_NAME) FNameEntryId DisplayIndex;
     } else {
         // UE 4.23+: Modern version with FNameEntryId
         FNameEntryId ComparisonIndex;
@@ -104,8 +109,97 @@ struct alignas(16) FTokenStreamOwner {
 
 struct FString : TArray<wchar_t> {};
 
+/// TEST
 struct FText {
-    uint64_t Placeholder[3];
+    if (UE_VERSION >= 501) {
+        STUB* TextData; // TRefCountPtr<ITextData>
+    } else {
+        STUB* TextData; // TSharedRef<ITextData, ESPMode::ThreadSafe>
+        STUB* TextDataController;
+    }
+    uint32_t Flags; // ETextFlag
+};
+
+/// Interned localization key string
+/// TEST
+struct FTextKey {
+    wchar_t* StrPtr;
+    uint32_t StrHash;
+};
+
+/// TEST
+struct FTextId {
+    FTextKey Namespace;
+    FTextKey Key;
+};
+
+/// TEST
+class FTextHistory {
+    uint64_t VTable;
+    if (UE_VERSION >= 501) int32_t RefCount;
+    if (UE_VERSION < 500) uint16_t Revision;
+};
+
+/// Pre-5.0 ITextData implementation (TTextData<FTextHistory_*>) which wraps the history.
+/// From 5.0 onwards FTextHistory implements ITextData directly.
+/// TEST
+class FTextData {
+    uint64_t VTable;
+    FString* LocalizedString; // FTextDisplayStringPtr
+    STUB* LocalizedStringController;
+    FTextHistory History;
+};
+
+/// Virtual functions of pre-5.0 ITextData up to the ones read by the dumper
+struct FTextDataVTable {
+    STUB* Destructor;
+    STUB* GetDisplayString;
+};
+
+/// Virtual functions of FTextHistory up to GetType. From 5.0 onwards FTextHistory implements
+/// ITextData and its virtuals come first.
+struct FTextHistoryVTable {
+    STUB* Destructor;
+    // ITextData is reference counted by TRefCountPtr from 5.1
+    if (UE_VERSION >= 501) {
+        STUB* AddRef;
+        STUB* Release;
+    }
+    if (UE_VERSION >= 500) {
+        STUB* GetSourceString;
+        STUB* GetDisplayString;
+        STUB* GetLocalizedString;
+        STUB* GetGlobalHistoryRevision;
+        STUB* GetLocalHistoryRevision;
+        STUB* GetTextHistory;
+    }
+    STUB* GetType;
+};
+
+/// Culture invariant text
+/// TEST
+class FTextHistory_None : FTextHistory {
+    if (UE_VERSION >= 500) {
+        FTextId TextId;
+        FString DisplayString;
+    }
+};
+
+/// Localized text. Before 4.26 the namespace and key are not stored on the history
+/// TEST
+class FTextHistory_Base : FTextHistory {
+    if (UE_VERSION >= 426) FTextId TextId;
+    FString SourceString;
+    if (UE_VERSION >= 500) {
+        FString* LocalizedString; // FTextConstDisplayStringPtr
+        STUB* LocalizedStringController;
+    }
+};
+
+/// Base for all histories which generate their display string (AsNumber, AsDate, formats, etc.)
+/// TEST
+class FTextHistory_Generated : FTextHistory {
+    if (UE_VERSION >= 500) FString DisplayString;
 };
 
 type EInternalObjectFlags = int32_t;
@@ -118,6 +212,89 @@ struct FGuid {
     uint32_t D;
 };
 
+/// TEST
+struct FWeakObjectPtr {
+    int32_t ObjectIndex;
+    int32_t ObjectSerialNumber;
+};
+
+/// TEST
+struct FScriptDelegate {
+    FWeakObjectPtr Object;
+    FName FunctionName;
+};
+
+/// TEST
+struct FMulticastScriptDelegate {
+    TArray<FScriptDelegate> InvocationList;
+};
+
+// Element of FSparseDelegateStorage::SparseDelegates
+// TSetElement<TTuple<const UObjectBase*, TMap<FName, TSharedPtr<FMulticastScriptDelegate>>>>
+/// TEST
+struct FSparseDelegateObjectElement {
+    UObject* Object;
+    TMap<FName, STUB*> Delegates;
+    int32_t HashNextId;
+    int32_t HashIndex;
+};
+
+// Element of the per-object map: TSetElement<TTuple<FName, TSharedPtr<FMulticastScriptDelegate>>>
+/// TEST
+struct FSparseDelegateElement {
+    FName DelegateName;
+    FMulticastScriptDelegate* Delegate;
+    STUB* DelegateReferenceController;
+    int32_t HashNextId;
+    int32_t HashIndex;
+};
+
+/// TEST
+struct FUniqueObjectGuid {
+    FGuid Guid;
+};
+
+/// Asset path split into package and asset names (UE 5.1+)
+/// TEST
+struct FTopLevelAssetPath {
+    FName PackageName;
+    FName AssetName;
+};
+
+/// TEST
+struct FSoftObjectPath {
+    if (UE_VERSION >= 501) FTopLevelAssetPath AssetPath;
+    else FName AssetPathName;
+    FString SubPathString;
+};
+
+template<typename TObjectID>
+struct TPersistentObjectPtr {
+    FWeakObjectPtr WeakPtr;
+    int32_t TagAtLastTest;
+    TObjectID ObjectID;
+};
+
+/// TEST
+struct FSoftObjectPtr : TPersistentObjectPtr<FSoftObjectPath> {};
+
+/// TEST
+struct FLazyObjectPtr : TPersistentObjectPtr<FUniqueObjectGuid> {};
+
+// Editor only InitialFieldClass and FieldPathSerialNumber are omitted
+/// TEST
+struct FFieldPath {
+    FField* ResolvedField;
+    FWeakObjectPtr ResolvedOwner;
+    TArray<FName> Path;
+};
+
+/// TEST
+struct FScriptInterface {
+    UObject* ObjectPointer;
+    STUB* InterfacePointer;
+};
+
 /// TEST
 struct FIoHash {
     uint8_t Hash[20];
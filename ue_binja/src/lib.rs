@@ -17,7 +17,10 @@ use binaryninja::{
 };
 use log::{error, info};
 
-use jmap::{Class, EClassCastFlags, Jmap, ObjectType, Property, PropertyType, Struct};
+use jmap::{
+    EClassCastFlags, Jmap, ObjectType, Property, PropertyType, Struct,
+    index::{JmapIndex, ObjectPath},
+};
 
 struct ImportCommand {}
 impl Command for ImportCommand {
@@ -54,9 +57,11 @@ impl Command for ImportCommand {
 }
 
 fn load(path: std::path::PathBuf) -> Result<Jmap> {
-    Ok(serde_json::from_reader(std::io::BufReader::new(
-        std::fs::File::open(path)?,
-    ))?)
+    let data = std::fs::read(path)?;
+    if jmap::binary::is_binary(&data) {
+        return Ok(jmap::binary::JmapBinary::new(data)?.to_jmap()?);
+    }
+    jmap::migrate::from_slice(&data)
 }
 
 #[unsafe(no_mangle)]
@@ -78,6 +83,7 @@ struct Ctx<'ref_data, 'types, 'bv> {
     bv: &'bv BinaryView,
 
     ref_data: &'ref_data Jmap,
+    index: JmapIndex<'ref_data>,
     store: &'types mut TypeStore<'ref_data>,
 }
 
@@ -122,7 +128,7 @@ impl<'a> std::ops::Index<TypeId> for TypeStore<'a> {
 
 fn obj_name(ref_data: &Jmap, path: &str) -> String {
     let obj = &ref_data.objects[path];
-    let name = path.rsplit(['/', '.', ':']).next().unwrap();
+    let name = ObjectPath::parse(path).name();
     match obj {
         ObjectType::Object(_) => name.to_string(),
         ObjectType::Package(_) => name.to_string(),
@@ -151,6 +157,7 @@ pub fn into_header(ref_data: &Jmap, bv: &BinaryView, filter: impl Fn(&str, &Obje
     Ctx {
         bv,
         ref_data,
+        index: ref_data.index(),
         store: &mut TypeStore::default(),
     }
     .generate(filter)
@@ -183,7 +190,7 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
             PropertyType::Str => CType::FString,
             PropertyType::Name => CType::FName,
             PropertyType::Text => CType::FText,
-            PropertyType::FieldPath => CType::FFieldPath,
+            PropertyType::FieldPath { .. } => CType::FFieldPath,
             PropertyType::MulticastInlineDelegate { .. } => CType::MulticastInlineDelegate, // TODO
             PropertyType::MulticastSparseDelegate { .. } => CType::MulticastSparseDelegate, // TODO
             PropertyType::MulticastDelegate { .. } => CType::MulticastDelegate,             // TODO
@@ -542,7 +549,7 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
             CType::UEEnum(_) => {}
             CType::UEStruct(path) => {
                 let struct_ = &self.ref_data.objects[path].get_struct().unwrap();
-                if let Some(super_) = &struct_.super_struct {
+                if let Some((super_, _)) = self.index.super_chain(path).nth(1) {
                     let super_id = self.store.insert(CType::UEStruct(super_));
                     dependencies.push((DepType::Full, super_id));
                 }
@@ -551,9 +558,9 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
                     dependencies.push((DepType::Full, prop_id));
                 }
             }
-            CType::UEClass(class) => {
-                let class = &self.ref_data.objects[class].get_class().unwrap();
-                if let Some(super_) = &class.r#struct.super_struct {
+            CType::UEClass(path) => {
+                let class = &self.ref_data.objects[path].get_class().unwrap();
+                if let Some((super_, _)) = self.index.super_chain(path).nth(1) {
                     let super_id = self.store.insert(CType::UEClass(super_));
                     dependencies.push((DepType::Full, super_id));
                 }
@@ -870,8 +877,7 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
 
                 let mut builder = Structure::builder();
 
-                if let Some(parent) = &struct_.super_struct {
-                    let parent_struct = &self.ref_data.objects[parent].get_struct().unwrap();
+                if let Some((parent, parent_struct)) = self.index.super_chain(path).nth(1) {
                     let parent = NamedTypeReference::new(
                         NamedTypeReferenceClass::StructNamedTypeClass,
                         obj_name(self.ref_data, parent),
@@ -936,40 +942,29 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
         let image_base = self.bv.original_image_base();
         let og_base = self.ref_data.image_base_address;
 
-        fn get_class<'a>(ref_data: &'a Jmap, class: &str) -> &'a Class {
-            ref_data.objects.get(class).unwrap().get_class().unwrap()
-        }
+        /// Base-most class reachable from `class` through super classes within `in_set`
         fn get_parent_in<'a>(
-            ref_data: &'a Jmap,
-            mut class: &'a str,
+            index: &JmapIndex<'a>,
+            class: &'a str,
             in_set: &HashSet<&'a str>,
         ) -> &'a str {
-            loop {
-                let class_obj = get_class(ref_data, class);
-                if let Some(parent) = class_obj.r#struct.super_struct.as_deref() {
-                    if in_set.contains(parent) {
-                        class = parent;
-                        continue;
-                    }
-                }
-                break;
-            }
-            class
+            index
+                .super_chain(class)
+                .map(|(path, _)| path)
+                .take_while(|path| *path == class || in_set.contains(path))
+                .last()
+                .unwrap_or(class)
         }
 
         let mut vtable_func_map: HashMap<u64, HashMap<usize, HashSet<&str>>> = Default::default();
 
         {
-            fn vtable_len(ref_data: &Jmap, class: &str) -> usize {
-                let mut class = Some(class);
-                while let Some(next) = class {
-                    let obj = ref_data.objects.get(next).unwrap().get_class().unwrap();
-                    if let Some(vtable) = obj.instance_vtable {
-                        return ref_data.vtables.get(&vtable).unwrap().len();
-                    }
-                    class = obj.r#struct.super_struct.as_deref();
-                }
-                0
+            /// Length of the vtable of `class` or its closest super class which has one
+            fn vtable_len(index: &JmapIndex<'_>, class: &str) -> usize {
+                index
+                    .super_chain(class)
+                    .find_map(|(path, _)| index.get_class(path)?.instance_vtable)
+                    .map_or(0, |vtable| index.jmap().vtables[&vtable].len())
             }
 
             for (path, obj) in &self.ref_data.objects {
@@ -983,14 +978,15 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
                     let mut builder = Structure::builder();
                     builder.propagates_data_var_refs(true);
 
-                    let len = vtable_len(self.ref_data, path);
-                    let parent_len = if let Some(parent) = &class.r#struct.super_struct {
+                    let len = vtable_len(&self.index, path);
+                    let parent = self.index.super_chain(path).nth(1);
+                    let parent_len = if let Some((parent, _)) = parent {
                         let parent_name = obj_name(self.ref_data, parent);
                         let parent_type = NamedTypeReference::new(
                             NamedTypeReferenceClass::StructNamedTypeClass,
                             format!("{parent_name}::VTable"),
                         );
-                        let parent_len = vtable_len(self.ref_data, parent);
+                        let parent_len = vtable_len(&self.index, parent);
                         builder.base_structures(&[BaseStructure {
                             ty: parent_type,
                             offset: 0,
@@ -1074,7 +1070,7 @@ impl<'ref_data> Ctx<'ref_data, '_, '_> {
                 let (index, refs) = refs.iter().next().unwrap();
                 let mut roots = HashSet::new();
                 for r in refs {
-                    roots.insert(get_parent_in(self.ref_data, r, refs));
+                    roots.insert(get_parent_in(&self.index, r, refs));
                 }
                 if roots.len() != 1 {
                     continue;
@@ -1,29 +1,77 @@
-use anyhow::Result;
-use std::io::Write;
+use anyhow::{Context, Result, bail};
+use std::io::{Read, Write};
+use std::sync::RwLock;
 
 use crate::CompressionMethod;
 
-pub fn compress<S: Write>(
-    compression: CompressionMethod,
-    input: &[u8],
-    mut output: S,
-) -> Result<()> {
-    match compression {
+/// Compression method and level used when writing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compression {
+    pub method: CompressionMethod,
+    /// Codec specific level, codec default if None
+    pub level: Option<i32>,
+}
+impl From<CompressionMethod> for Compression {
+    fn from(method: CompressionMethod) -> Self {
+        Self {
+            method,
+            level: None,
+        }
+    }
+}
+
+/// Oodle is proprietary so no implementation is bundled. Register one with [`set_oodle`]
+/// (e.g. backed by a dynamically loaded oo2core library) to read and write Oodle usmaps.
+pub trait OodleCodec: Send + Sync {
+    fn compress(&self, input: &[u8], level: Option<i32>) -> Result<Vec<u8>>;
+    /// Decompress `input` filling all of `output`
+    fn decompress(&self, input: &[u8], output: &mut [u8]) -> Result<()>;
+}
+
+static OODLE: RwLock<Option<Box<dyn OodleCodec>>> = RwLock::new(None);
+
+/// Register the codec used for [`CompressionMethod::Oodle`], replacing any previous one
+pub fn set_oodle(codec: impl OodleCodec + 'static) {
+    *OODLE.write().unwrap() = Some(Box::new(codec));
+}
+
+fn with_oodle<T>(f: impl FnOnce(&dyn OodleCodec) -> Result<T>) -> Result<T> {
+    match OODLE.read().unwrap().as_deref() {
+        Some(oodle) => f(oodle),
+        None => {
+            bail!("no Oodle codec registered, see usmap::set_oodle")
+        }
+    }
+}
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_DEFAULT_QUALITY: i32 = 9;
+const BROTLI_LG_WINDOW_SIZE: u32 = 22;
+
+pub fn compress<S: Write>(compression: Compression, input: &[u8], mut output: S) -> Result<()> {
+    match compression.method {
         CompressionMethod::Zstd => {
-            let buf = zstd::stream::encode_all(input, 0)?;
+            let buf = zstd::stream::encode_all(input, compression.level.unwrap_or(0))?;
             output.write_all(&buf)?;
         }
         CompressionMethod::Oodle => {
-            todo!()
-            //let buffer = oodle_loader::oodle()?.compress(
-            //    input,
-            //    oodle_loader::Compressor::Mermaid,
-            //    oodle_loader::CompressionLevel::Normal,
-            //)?;
-            //output.write_all(&buffer)?;
+            let buffer = with_oodle(|oodle| oodle.compress(input, compression.level))?;
+            output.write_all(&buffer)?;
         }
         CompressionMethod::Brotli => {
-            todo!()
+            let quality = compression.level.unwrap_or(BROTLI_DEFAULT_QUALITY);
+            if !(0..=11).contains(&quality) {
+                bail!("Brotli quality must be between 0 and 11, got {quality}");
+            }
+            let mut writer = brotli::CompressorWriter::new(
+                &mut output,
+                BROTLI_BUFFER_SIZE,
+                quality as u32,
+                BROTLI_LG_WINDOW_SIZE,
+            );
+            writer.write_all(input)?;
+            // finishes the stream
+            writer.into_inner();
         }
     }
     Ok(())
@@ -35,18 +83,12 @@ pub fn decompress(compression: CompressionMethod, input: &[u8], output: &mut [u8
             zstd::bulk::decompress_to_buffer(input, output)?;
         }
         CompressionMethod::Oodle => {
-            todo!()
-            //let status = oodle_loader::oodle()?.decompress(input, output);
-            //if status < 0 || status as usize != output.len() {
-            //    bail!(
-            //        "Oodle decompression failed: expected {} output bytes, got {}",
-            //        output.len(),
-            //        status,
-            //    );
-            //}
+            with_oodle(|oodle| oodle.decompress(input, output))?;
         }
         CompressionMethod::Brotli => {
-            todo!()
+            brotli::Decompressor::new(input, BROTLI_BUFFER_SIZE)
+                .read_exact(output)
+                .context("Brotli decompression failed")?;
         }
     }
     Ok(())
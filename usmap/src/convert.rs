@@ -0,0 +1,526 @@
+//! Conversion of a usmap into a skeletal jmap.
+//!
+//! usmap only carries names, property types and (with extensions) package paths and flags.
+//! Everything else is unknown: addresses, vtables, property offsets and sizes are set to 0 and
+//! the jmap is marked [`jmap::Jmap::layout_unknown`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use jmap::{Address, EClassCastFlags, EClassFlags, EEnumFlags, EPropertyFlags, EStructFlags};
+
+use crate::{FlagsType, PropertyInner, Usmap};
+
+/// Package used for types when the usmap has no PPTH extension
+pub const UNKNOWN_PACKAGE: &str = "/Script/Unknown";
+
+impl Usmap {
+    /// Reconstruct enums, structs and classes as a jmap. Properties of a type with no jmap
+    /// equivalent (`PropertyInner::Unknown`) are skipped. Types which would end up at the same
+    /// path (same name without PPTH) are renamed and reported in [`jmap::Jmap::diagnostics`].
+    pub fn to_jmap(&self) -> jmap::Jmap {
+        let ppth = self.ppth.as_ref();
+        let eatr = self.eatr.as_ref();
+        let envp = self.envp.as_ref();
+
+        let mut diagnostics = vec![];
+        let mut taken = HashSet::new();
+
+        let enum_paths: Vec<String> = self
+            .enums
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let package = ppth.and_then(|p| p.enums.get(i));
+                unique_path(type_path(package, &e.name), &mut taken, &mut diagnostics)
+            })
+            .collect();
+        let struct_paths: Vec<String> = self
+            .structs
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let package = ppth.and_then(|p| p.structs.get(i));
+                unique_path(type_path(package, &s.name), &mut taken, &mut diagnostics)
+            })
+            .collect();
+
+        let mut ctx = Ctx {
+            enums: HashMap::new(),
+            structs: HashMap::new(),
+            supers: HashMap::new(),
+        };
+        for (e, path) in self.enums.iter().zip(&enum_paths) {
+            ctx.enums.entry(e.name.as_str()).or_insert(path.as_str());
+        }
+        for (s, path) in self.structs.iter().zip(&struct_paths) {
+            ctx.structs.entry(s.name.as_str()).or_insert(path.as_str());
+            ctx.supers
+                .entry(s.name.as_str())
+                .or_insert(s.super_struct.as_deref());
+        }
+
+        let mut objects = BTreeMap::new();
+        let mut packages: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+        for (i, (e, path)) in self.enums.iter().zip(&enum_paths).enumerate() {
+            let names: Vec<(String, i64)> = match envp.and_then(|envp| envp.value_pairs.get(i)) {
+                Some(pairs) => pairs
+                    .iter()
+                    .map(|(name, value)| (name.clone(), *value as i64))
+                    .collect(),
+                None => e
+                    .entries
+                    .iter()
+                    .map(|(value, name)| (format!("{}::{name}", e.name), *value))
+                    .collect(),
+            };
+            // UEnum names are qualified ("EFoo::A") for enum class and namespaced enums, which
+            // usmap cannot tell apart, and bare for regular enums. Without ENVP only bare names
+            // are known, they are qualified and the enum treated as the far more common enum
+            // class.
+            let cpp_form = if names.iter().any(|(name, _)| name.contains("::")) {
+                jmap::ECppForm::EnumClass
+            } else {
+                jmap::ECppForm::Regular
+            };
+            let enum_flags = eatr
+                .and_then(|eatr| eatr.enum_flags.get(i))
+                .map(|f| EEnumFlags::from_bits_retain(*f as u8));
+
+            let package = package_of(path);
+            packages
+                .entry(package.to_string())
+                .or_default()
+                .insert(path.clone());
+            objects.insert(
+                path.clone(),
+                jmap::ObjectType::Enum(jmap::Enum {
+                    object: jmap::Object::new("/Script/CoreUObject.Enum", Some(package.into())),
+                    cpp_type: e.name.clone(),
+                    enum_flags,
+                    cpp_form,
+                    names,
+                }),
+            );
+        }
+
+        for (i, (s, path)) in self.structs.iter().zip(&struct_paths).enumerate() {
+            let flags = eatr.and_then(|eatr| eatr.struct_flags.get(i));
+            let is_class = match flags.map(|f| f.type_) {
+                Some(FlagsType::Class) => true,
+                Some(FlagsType::Struct) => false,
+                _ => ctx.is_object_derived(&s.name),
+            };
+
+            let mut properties = vec![];
+            for (j, prop) in s.properties.iter().enumerate() {
+                let Some(r#type) = ctx.property_type(&prop.inner) else {
+                    continue;
+                };
+                let prop_flags = flags
+                    .and_then(|f| f.prop_flags.get(j))
+                    .map_or(EPropertyFlags::empty(), |f| {
+                        EPropertyFlags::from_bits_retain(*f)
+                    });
+                properties.push(jmap::Property {
+                    array_dim: prop.array_dim as usize,
+                    flags: prop_flags,
+                    ..jmap::Property::new(&prop.name, r#type)
+                });
+            }
+
+            let package = package_of(path);
+            let class = if is_class {
+                "/Script/CoreUObject.Class"
+            } else {
+                "/Script/CoreUObject.ScriptStruct"
+            };
+            let r#struct = jmap::Struct {
+                object: jmap::Object::new(class, Some(package.into())),
+                super_struct: s.super_struct.as_deref().map(|s| ctx.struct_path(s)),
+                properties,
+                properties_size: 0,
+                min_alignment: 0,
+                script: vec![],
+            };
+            let flags = flags.map_or(0, |f| f.value);
+            let obj = if is_class {
+                jmap::ObjectType::Class(jmap::Class {
+                    r#struct,
+                    class_flags: EClassFlags::from_bits_retain(flags),
+                    class_cast_flags: EClassCastFlags::empty(),
+                    class_default_object: None,
+                    instance_vtable: None,
+                })
+            } else {
+                jmap::ObjectType::ScriptStruct(jmap::ScriptStruct {
+                    r#struct,
+                    struct_flags: EStructFlags::from_bits_retain(flags),
+                })
+            };
+            packages
+                .entry(package.to_string())
+                .or_default()
+                .insert(path.clone());
+            objects.insert(path.clone(), obj);
+        }
+
+        for (package, children) in packages {
+            let mut object = jmap::Object::new("/Script/CoreUObject.Package", None);
+            object.children = children;
+            objects.insert(package, jmap::ObjectType::Package(jmap::Package { object }));
+        }
+
+        jmap::Jmap {
+            format_version: jmap::FORMAT_VERSION,
+            metadata: None,
+            image_base_address: Address(0),
+            layout_unknown: true,
+            objects,
+            vtables: Default::default(),
+            names: None,
+            diagnostics,
+        }
+    }
+}
+
+/// Full paths and super structs of types by short name
+struct Ctx<'a> {
+    enums: HashMap<&'a str, &'a str>,
+    structs: HashMap<&'a str, &'a str>,
+    supers: HashMap<&'a str, Option<&'a str>>,
+}
+impl<'a> Ctx<'a> {
+    /// Whether the struct is `Object` or inherits from it, used to tell classes apart from
+    /// script structs when there is no EATR extension
+    fn is_object_derived(&self, mut name: &'a str) -> bool {
+        // bounded in case of cyclic super structs
+        for _ in 0..=self.supers.len() {
+            if name == "Object" {
+                return true;
+            }
+            match self.supers.get(name) {
+                Some(&Some(super_struct)) => name = super_struct,
+                _ => return false,
+            }
+        }
+        false
+    }
+    fn struct_path(&self, name: &str) -> String {
+        match self.structs.get(name) {
+            Some(path) => path.to_string(),
+            None => type_path(None, name),
+        }
+    }
+    fn enum_path(&self, name: &str) -> Option<String> {
+        if name == "None" {
+            return None;
+        }
+        Some(match self.enums.get(name) {
+            Some(path) => path.to_string(),
+            None => type_path(None, name),
+        })
+    }
+    fn property_type(&self, inner: &PropertyInner) -> Option<jmap::PropertyType> {
+        use PropertyInner as PI;
+        use jmap::PropertyType as PT;
+
+        let object_class = || "/Script/CoreUObject.Object".to_string();
+        let boxed = |inner: &PropertyInner| -> Option<Box<jmap::Property>> {
+            Some(jmap::Property::new("", self.property_type(inner)?).into())
+        };
+        Some(match inner {
+            PI::Byte => PT::Byte { r#enum: None },
+            PI::Bool => PT::Bool {
+                field_size: 1,
+                byte_offset: 0,
+                byte_mask: 0xff,
+                field_mask: 0xff,
+            },
+            PI::Int => PT::Int,
+            PI::Float => PT::Float,
+            PI::Object => PT::Object {
+                property_class: object_class(),
+            },
+            PI::Name => PT::Name,
+            PI::Delegate => PT::Delegate {
+                signature_function: None,
+            },
+            PI::Double => PT::Double,
+            PI::Array { inner } => PT::Array {
+                inner: boxed(inner)?,
+            },
+            PI::Struct { name } => PT::Struct {
+                r#struct: self.struct_path(name),
+            },
+            PI::Str => PT::Str,
+            PI::Text => PT::Text,
+            PI::Interface => PT::Interface {
+                interface_class: "/Script/CoreUObject.Interface".to_string(),
+            },
+            PI::MulticastDelegate => PT::MulticastDelegate {
+                signature_function: None,
+            },
+            PI::WeakObject => PT::WeakObject {
+                property_class: object_class(),
+            },
+            PI::LazyObject => PT::LazyObject {
+                property_class: object_class(),
+            },
+            PI::AssetObject | PI::SoftObject => PT::SoftObject {
+                property_class: object_class(),
+            },
+            PI::UInt64 => PT::UInt64,
+            PI::UInt32 => PT::UInt32,
+            PI::UInt16 => PT::UInt16,
+            PI::Int64 => PT::Int64,
+            PI::Int16 => PT::Int16,
+            PI::Int8 => PT::Int8,
+            PI::Map { key, value } => PT::Map {
+                key_prop: boxed(key)?,
+                value_prop: boxed(value)?,
+            },
+            PI::Set { key } => PT::Set {
+                key_prop: boxed(key)?,
+            },
+            // inverse of the ByteProperty special case when writing usmap
+            PI::Enum { inner, name } if **inner == PI::Byte => PT::Byte {
+                r#enum: self.enum_path(name),
+            },
+            PI::Enum { inner, name } => PT::Enum {
+                container: boxed(inner)?,
+                r#enum: self.enum_path(name),
+            },
+            PI::FieldPath => PT::FieldPath {
+                property_class: None,
+            },
+            PI::Optional { inner } => PT::Optional {
+                inner: boxed(inner)?,
+            },
+            PI::Utf8Str => PT::Utf8Str,
+            PI::AnsiStr => PT::AnsiStr,
+            PI::Unknown => return None,
+        })
+    }
+}
+
+fn type_path(package: Option<&String>, name: &str) -> String {
+    format!("{}.{name}", package.map_or(UNKNOWN_PACKAGE, |p| p.as_str()))
+}
+
+/// Rename `path` with a numeric suffix if another type already took it. References by name
+/// keep resolving to the first type of that name.
+fn unique_path(
+    path: String,
+    taken: &mut HashSet<String>,
+    diagnostics: &mut Vec<jmap::Diagnostic>,
+) -> String {
+    if taken.insert(path.clone()) {
+        return path;
+    }
+    let unique = (1..)
+        .map(|i| format!("{path}_{i}"))
+        .find(|p| !taken.contains(p))
+        .unwrap();
+    taken.insert(unique.clone());
+    diagnostics.push(jmap::Diagnostic {
+        index: None,
+        path: Some(unique.clone()),
+        property: None,
+        error: vec![format!(
+            "multiple types map to {path}, renamed to {unique} (usmap without PPTH?)"
+        )],
+    });
+    unique
+}
+
+fn package_of(path: &str) -> &str {
+    path.split('.').next().unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use jmap::{ObjectType, PropertyType};
+
+    use super::*;
+    use crate::{Enum, ExtPpth, Property, Struct};
+
+    fn prop(name: &str, inner: PropertyInner) -> Property {
+        Property {
+            name: name.into(),
+            array_dim: 1,
+            index: 0,
+            inner,
+        }
+    }
+
+    #[test]
+    fn test_to_jmap() {
+        let usmap = Usmap {
+            enums: vec![Enum {
+                name: "ETest".into(),
+                entries: [(0, "A".into()), (1, "B".into())].into(),
+            }],
+            structs: vec![
+                Struct {
+                    name: "Object".into(),
+                    super_struct: None,
+                    properties: vec![],
+                },
+                Struct {
+                    name: "Actor".into(),
+                    super_struct: Some("Object".into()),
+                    properties: vec![
+                        prop(
+                            "Location",
+                            PropertyInner::Struct {
+                                name: "Vector".into(),
+                            },
+                        ),
+                        prop(
+                            "Mode",
+                            PropertyInner::Enum {
+                                inner: PropertyInner::Byte.into(),
+                                name: "ETest".into(),
+                            },
+                        ),
+                        prop("Unsupported", PropertyInner::Unknown),
+                        prop(
+                            "Tags",
+                            PropertyInner::Array {
+                                inner: PropertyInner::Name.into(),
+                            },
+                        ),
+                    ],
+                },
+                Struct {
+                    name: "Vector".into(),
+                    super_struct: None,
+                    properties: vec![prop("X", PropertyInner::Double)],
+                },
+            ],
+            cext: None,
+            ppth: Some(ExtPpth {
+                version: 0,
+                enums: vec!["/Script/Test".into()],
+                structs: vec![
+                    "/Script/CoreUObject".into(),
+                    "/Script/Engine".into(),
+                    "/Script/CoreUObject".into(),
+                ],
+            }),
+            eatr: None,
+            envp: None,
+        };
+        let jmap = usmap.to_jmap();
+        assert!(jmap.layout_unknown);
+
+        let Some(ObjectType::Enum(e)) = jmap.objects.get("/Script/Test.ETest") else {
+            panic!("ETest not converted to an enum");
+        };
+        assert_eq!(
+            e.names,
+            [("ETest::A".to_string(), 0), ("ETest::B".to_string(), 1)]
+        );
+        assert!(matches!(e.cpp_form, jmap::ECppForm::EnumClass));
+
+        assert!(matches!(
+            jmap.objects.get("/Script/CoreUObject.Vector"),
+            Some(ObjectType::ScriptStruct(_))
+        ));
+        let Some(ObjectType::Class(actor)) = jmap.objects.get("/Script/Engine.Actor") else {
+            panic!("Actor not converted to a class");
+        };
+        let actor = &actor.r#struct;
+        assert_eq!(
+            actor.super_struct.as_deref(),
+            Some("/Script/CoreUObject.Object")
+        );
+        let names: Vec<_> = actor.properties.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Location", "Mode", "Tags"]);
+        assert!(matches!(
+            &actor.properties[0].r#type,
+            PropertyType::Struct { r#struct } if r#struct == "/Script/CoreUObject.Vector"
+        ));
+        assert!(matches!(
+            &actor.properties[1].r#type,
+            PropertyType::Byte { r#enum: Some(e) } if e == "/Script/Test.ETest"
+        ));
+
+        let engine = jmap.objects["/Script/Engine"].get_object();
+        assert!(engine.children.contains("/Script/Engine.Actor"));
+
+        // all offsets and sizes are 0 but must not be reported as broken layouts
+        let layout_issues = jmap.validate().into_iter().filter(|i| {
+            matches!(
+                i.kind,
+                jmap::validate::IssueKind::OutOfBounds { .. }
+                    | jmap::validate::IssueKind::Overlap { .. }
+            )
+        });
+        assert_eq!(layout_issues.count(), 0);
+    }
+
+    #[test]
+    fn test_to_jmap_name_collisions() {
+        let vector = |x: &str| Struct {
+            name: "Vector".into(),
+            super_struct: None,
+            properties: vec![prop(x, PropertyInner::Double)],
+        };
+        let usmap = Usmap {
+            enums: vec![Enum {
+                name: "Vector".into(),
+                entries: [(0, "A".into())].into(),
+            }],
+            structs: vec![
+                vector("X"),
+                vector("Y"),
+                Struct {
+                    name: "Actor".into(),
+                    super_struct: None,
+                    properties: vec![prop(
+                        "Location",
+                        PropertyInner::Struct {
+                            name: "Vector".into(),
+                        },
+                    )],
+                },
+            ],
+            cext: None,
+            ppth: None,
+            eatr: None,
+            envp: None,
+        };
+        let jmap = usmap.to_jmap();
+
+        assert!(matches!(
+            jmap.objects.get("/Script/Unknown.Vector"),
+            Some(ObjectType::Enum(_))
+        ));
+        let property_names = |path: &str| -> Vec<String> {
+            let Some(ObjectType::ScriptStruct(s)) = jmap.objects.get(path) else {
+                panic!("{path} not converted to a script struct");
+            };
+            s.r#struct
+                .properties
+                .iter()
+                .map(|p| p.name.clone())
+                .collect()
+        };
+        assert_eq!(property_names("/Script/Unknown.Vector_1"), ["X"]);
+        assert_eq!(property_names("/Script/Unknown.Vector_2"), ["Y"]);
+        let package = jmap.objects["/Script/Unknown"].get_object();
+        assert_eq!(package.children.len(), 4);
+
+        let renamed: Vec<_> = jmap
+            .diagnostics
+            .iter()
+            .map(|d| d.path.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            renamed,
+            ["/Script/Unknown.Vector_1", "/Script/Unknown.Vector_2"]
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Comparison of two usmaps, e.g. to validate one dumper's output against another's
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use jmap::diff::{ChangeKind, Differ, JmapDiff};
+
+use crate::{PropertyInner, Struct, Usmap};
+
+/// Same changes and output as a jmap diff, with struct and enum names in place of paths
+pub type UsmapDiff = JmapDiff;
+
+impl Usmap {
+    /// Compare enums and structs by name. Enums are listed before structs, each ordered by name.
+    pub fn diff(&self, new: &Usmap) -> UsmapDiff {
+        let old = self;
+        let mut d = Differ::default();
+
+        let old_enums: BTreeMap<_, _> = old.enums.iter().map(|e| (&e.name, e)).collect();
+        let new_enums: BTreeMap<_, _> = new.enums.iter().map(|e| (&e.name, e)).collect();
+        for name in union(old_enums.keys(), new_enums.keys()) {
+            match (old_enums.get(name), new_enums.get(name)) {
+                (Some(_), None) => d.push(name, None, ChangeKind::Removed),
+                (None, Some(_)) => d.push(name, None, ChangeKind::Added),
+                (Some(old), Some(new)) => {
+                    let old_entries: BTreeMap<_, _> =
+                        old.entries.iter().map(|(v, n)| (n, v)).collect();
+                    let new_entries: BTreeMap<_, _> =
+                        new.entries.iter().map(|(v, n)| (n, v)).collect();
+                    for entry in union(old_entries.keys(), new_entries.keys()) {
+                        let member = Some(entry.as_str());
+                        match (old_entries.get(entry), new_entries.get(entry)) {
+                            (Some(_), None) => d.push(name, member, ChangeKind::Removed),
+                            (None, Some(_)) => d.push(name, member, ChangeKind::Added),
+                            (Some(old), Some(new)) => d.modified(name, member, "value", old, new),
+                            (None, None) => unreachable!(),
+                        }
+                    }
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+
+        let old_packages = package_paths(old);
+        let new_packages = package_paths(new);
+        let old_structs: BTreeMap<_, _> = old.structs.iter().map(|s| (&s.name, s)).collect();
+        let new_structs: BTreeMap<_, _> = new.structs.iter().map(|s| (&s.name, s)).collect();
+        for name in union(old_structs.keys(), new_structs.keys()) {
+            match (old_structs.get(name), new_structs.get(name)) {
+                (Some(_), None) => d.push(name, None, ChangeKind::Removed),
+                (None, Some(_)) => d.push(name, None, ChangeKind::Added),
+                (Some(old), Some(new)) => {
+                    // only comparable if both have the PPTH extension
+                    if let (Some(old), Some(new)) = (&old_packages, &new_packages) {
+                        d.modified(name, None, "package", old.get(name), new.get(name));
+                    }
+                    r#struct(&mut d, name, old, new);
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+
+        d.finish()
+    }
+}
+
+fn union<'a, T: Ord + 'a>(
+    a: impl Iterator<Item = &'a T>,
+    b: impl Iterator<Item = &'a T>,
+) -> BTreeSet<&'a T> {
+    a.chain(b).collect()
+}
+
+/// Package path of each struct by name from the PPTH extension
+fn package_paths(usmap: &Usmap) -> Option<HashMap<&String, &String>> {
+    let ppth = usmap.ppth.as_ref()?;
+    Some(
+        usmap
+            .structs
+            .iter()
+            .map(|s| &s.name)
+            .zip(&ppth.structs)
+            .collect(),
+    )
+}
+
+fn r#struct(d: &mut Differ, name: &str, old: &Struct, new: &Struct) {
+    d.modified(
+        name,
+        None,
+        "super_struct",
+        &old.super_struct,
+        &new.super_struct,
+    );
+
+    let old_props: BTreeMap<_, _> = old.properties.iter().map(|p| (&p.name, p)).collect();
+    let new_props: BTreeMap<_, _> = new.properties.iter().map(|p| (&p.name, p)).collect();
+    // report in property order of the new struct, removed properties last
+    let mut seen = BTreeSet::new();
+    let names = new
+        .properties
+        .iter()
+        .chain(&old.properties)
+        .map(|p| &p.name)
+        .filter(|n| seen.insert(*n));
+    for prop in names {
+        let member = Some(prop.as_str());
+        match (old_props.get(prop), new_props.get(prop)) {
+            (Some(_), None) => d.push(name, member, ChangeKind::Removed),
+            (None, Some(_)) => d.push(name, member, ChangeKind::Added),
+            (Some(old), Some(new)) => {
+                d.modified(name, member, "index", old.index, new.index);
+                d.modified(name, member, "array_dim", old.array_dim, new.array_dim);
+                d.modified(
+                    name,
+                    member,
+                    "type",
+                    type_string(&old.inner),
+                    type_string(&new.inner),
+                );
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+/// Compact description of a property type e.g. `Map<Name, Struct<Vector>>`
+pub fn type_string(inner: &PropertyInner) -> String {
+    use PropertyInner as PI;
+    match inner {
+        PI::Array { inner } => format!("Array<{}>", type_string(inner)),
+        PI::Struct { name } => format!("Struct<{name}>"),
+        PI::Map { key, value } => format!("Map<{}, {}>", type_string(key), type_string(value)),
+        PI::Set { key } => format!("Set<{}>", type_string(key)),
+        PI::Enum { inner, name } => format!("Enum<{}, {name}>", type_string(inner)),
+        PI::Optional { inner } => format!("Optional<{}>", type_string(inner)),
+        other => format!("{other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jmap::diff::Change;
+
+    use super::*;
+    use crate::{Enum, ExtPpth, Property};
+
+    fn prop(name: &str, index: u16, inner: PropertyInner) -> Property {
+        Property {
+            name: name.into(),
+            array_dim: 1,
+            index,
+            inner,
+        }
+    }
+
+    fn usmap(enums: Vec<Enum>, structs: Vec<Struct>, packages: Option<Vec<&str>>) -> Usmap {
+        Usmap {
+            enums,
+            structs,
+            cext: None,
+            ppth: packages.map(|structs| ExtPpth {
+                version: 0,
+                enums: vec![],
+                structs: structs.into_iter().map(str::to_string).collect(),
+            }),
+            eatr: None,
+            envp: None,
+        }
+    }
+
+    fn change(name: &str, member: Option<&str>, kind: ChangeKind) -> Change {
+        Change {
+            path: name.into(),
+            member: member.map(str::to_string),
+            kind,
+        }
+    }
+
+    fn modified(field: &str, old: &str, new: &str) -> ChangeKind {
+        ChangeKind::Modified {
+            field: field.into(),
+            old: old.into(),
+            new: new.into(),
+        }
+    }
+
+    #[test]
+    fn test_diff_enums() {
+        let old = usmap(
+            vec![
+                Enum {
+                    name: "EMode".into(),
+                    entries: [(0, "A".into()), (1, "B".into())].into(),
+                },
+                Enum {
+                    name: "EGone".into(),
+                    entries: Default::default(),
+                },
+            ],
+            vec![],
+            None,
+        );
+        let new = usmap(
+            vec![Enum {
+                name: "EMode".into(),
+                entries: [(0, "A".into()), (2, "B".into()), (3, "C".into())].into(),
+            }],
+            vec![],
+            None,
+        );
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.changes,
+            [
+                change("EGone", None, ChangeKind::Removed),
+                change(
+                    "EMode",
+                    Some("B"),
+                    ChangeKind::Modified {
+                        field: "value".into(),
+                        old: 1.into(),
+                        new: 2.into(),
+                    }
+                ),
+                change("EMode", Some("C"), ChangeKind::Added),
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "- EGone\n~ EMode:B value: 1 -> 2\n+ EMode:C\n"
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_structs() {
+        let old = usmap(
+            vec![],
+            vec![Struct {
+                name: "Thing".into(),
+                super_struct: Some("Object".into()),
+                properties: vec![
+                    prop("X", 0, PropertyInner::Int),
+                    prop("Y", 1, PropertyInner::Int),
+                ],
+            }],
+            Some(vec!["/Script/Old"]),
+        );
+        let new = usmap(
+            vec![],
+            vec![
+                Struct {
+                    name: "Thing".into(),
+                    super_struct: Some("Actor".into()),
+                    properties: vec![
+                        prop("Z", 0, PropertyInner::Float),
+                        prop(
+                            "X",
+                            1,
+                            PropertyInner::Array {
+                                inner: PropertyInner::Int.into(),
+                            },
+                        ),
+                    ],
+                },
+                Struct {
+                    name: "Vector".into(),
+                    super_struct: None,
+                    properties: vec![],
+                },
+            ],
+            Some(vec!["/Script/New", "/Script/CoreUObject"]),
+        );
+        assert_eq!(
+            old.diff(&new).changes,
+            [
+                change(
+                    "Thing",
+                    None,
+                    modified("package", "/Script/Old", "/Script/New")
+                ),
+                change("Thing", None, modified("super_struct", "Object", "Actor")),
+                change("Thing", Some("Z"), ChangeKind::Added),
+                change(
+                    "Thing",
+                    Some("X"),
+                    ChangeKind::Modified {
+                        field: "index".into(),
+                        old: 0.into(),
+                        new: 1.into(),
+                    }
+                ),
+                change("Thing", Some("X"), modified("type", "Int", "Array<Int>")),
+                change("Thing", Some("Y"), ChangeKind::Removed),
+                change("Vector", None, ChangeKind::Added),
+            ]
+        );
+    }
+}
@@ -1,7 +1,12 @@
 mod compression;
+mod convert;
+pub mod diff;
+
+pub use compression::{Compression, OodleCodec, set_oodle};
+pub use convert::UNKNOWN_PACKAGE;
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     io::{Read, Seek, Write},
 };
 
@@ -99,10 +104,11 @@ impl<S: Write> SerCtx<'_, S> {
         // split mutable borrow curse
         let (s, names) = (&mut self.inner, &self.names);
         for name in &names.names {
+            let too_long = || format!("name {name:?} too long for {:?}", self.header.version);
             if self.header.version >= UsmapVersion::LongFName {
-                s.write_u16::<LE>(name.len().try_into().expect("name too long"))?;
+                s.write_u16::<LE>(name.len().try_into().with_context(too_long)?)?;
             } else {
-                s.write_u8(name.len().try_into().expect("name too long"))?;
+                s.write_u8(name.len().try_into().with_context(too_long)?)?;
             };
             s.write_all(name.as_bytes())?;
         }
@@ -350,6 +356,41 @@ pub struct Header {
     pub compressed_size: u32,
     pub decompressed_size: u32,
 }
+/// Optional extension blocks written after the structs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Extension {
+    /// Package paths
+    Ppth,
+    /// Enum, struct and property flags
+    Eatr,
+    /// Full enum names and values
+    Envp,
+}
+
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// Target version. Writing fails if the data cannot be represented in it
+    pub version: UsmapVersion,
+    /// Uncompressed by default because FModel/UAssetAPI parsers seem to be broken for compressed usmaps
+    pub compression: Option<Compression>,
+    /// Extensions written if present
+    pub extensions: BTreeSet<Extension>,
+}
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            version: UsmapVersion::ExplicitEnumValues,
+            compression: None,
+            extensions: [Extension::Ppth, Extension::Eatr, Extension::Envp].into(),
+        }
+    }
+}
+impl WriteOptions {
+    fn includes(&self, extension: Extension) -> bool {
+        self.extensions.contains(&extension)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Struct {
     #[serde(rename = "$key$")]
@@ -466,13 +507,30 @@ impl Usmap {
             envp,
         })
     }
-    #[instrument(skip_all, name = "Usmap::write")]
+    /// Write with the default [`WriteOptions`]
     pub fn write<S: Write>(&self, s: &mut S) -> Result<()> {
+        self.write_with_options(s, &WriteOptions::default())
+    }
+    /// Write with the default [`WriteOptions`] and the given compression
+    pub fn write_compressed<S: Write>(
+        &self,
+        s: &mut S,
+        compression: Option<Compression>,
+    ) -> Result<()> {
+        let options = WriteOptions {
+            compression,
+            ..Default::default()
+        };
+        self.write_with_options(s, &options)
+    }
+    /// Write targeting a specific version, failing if the data cannot be represented in it
+    #[instrument(skip_all, name = "Usmap::write")]
+    pub fn write_with_options<S: Write>(&self, s: &mut S, options: &WriteOptions) -> Result<()> {
+        let compression = options.compression;
         let mut names = Names::new();
         let mut header = Header {
-            version: UsmapVersion::ExplicitEnumValues,
-            //compression_method: Some(CompressionMethod::Zstd),
-            compression_method: None, // disable compression because FModel/UAssetAPI parsers seem to be broken
+            version: options.version,
+            compression_method: compression.map(|c| c.method),
             compressed_size: 0,
             decompressed_size: 0,
         };
@@ -486,19 +544,37 @@ impl Usmap {
                 write_enums(s, &self.enums)?;
                 write_structs(s, &self.structs)?;
 
+                let ppth = self
+                    .ppth
+                    .as_ref()
+                    .filter(|_| options.includes(Extension::Ppth));
+                let eatr = self
+                    .eatr
+                    .as_ref()
+                    .filter(|_| options.includes(Extension::Eatr));
+                let envp = self
+                    .envp
+                    .as_ref()
+                    .filter(|_| options.includes(Extension::Envp));
+
+                // keep the extension count in sync with what is actually written
+                let num_ext = [ppth.is_some(), eatr.is_some(), envp.is_some()]
+                    .into_iter()
+                    .filter(|e| *e)
+                    .count() as u32;
                 if let Some(ext) = &self.cext {
                     s.write_all(b"CEXT")?;
-                    ext.write(s)?;
+                    ExtCext { num_ext, ..*ext }.write(s)?;
                 }
-                if let Some(ext) = &self.ppth {
+                if let Some(ext) = ppth {
                     s.write_all(b"PPTH")?;
                     ext.write(s)?;
                 }
-                if let Some(ext) = &self.eatr {
+                if let Some(ext) = eatr {
                     s.write_all(b"EATR")?;
                     ext.write(s)?;
                 }
-                if let Some(ext) = &self.envp {
+                if let Some(ext) = envp {
                     s.write_all(b"ENVP")?;
                     ext.write(s)?;
                 }
@@ -509,10 +585,10 @@ impl Usmap {
 
         header.compressed_size = full_buffer.len() as u32;
         header.decompressed_size = full_buffer.len() as u32;
-        let maybe_compressed = match header.compression_method {
-            Some(m) => {
+        let maybe_compressed = match compression {
+            Some(c) => {
                 let mut b = vec![];
-                compression::compress(m, &full_buffer, &mut b)?;
+                compression::compress(c, &full_buffer, &mut b)?;
                 header.compressed_size = b.len() as u32;
                 b
             }
@@ -615,11 +691,23 @@ fn write_enums<S: Write>(s: &mut SerCtx<S>, enums: &[Enum]) -> Result<()> {
     s.write_u32::<LE>(enums.len() as u32)?;
 
     for e in enums {
+        let version = s.header.version;
+        if version < UsmapVersion::ExplicitEnumValues
+            && e.entries.keys().enumerate().any(|(i, v)| *v != i as i64)
+        {
+            bail!(
+                "enum {} has non-sequential values which require {:?}",
+                e.name,
+                UsmapVersion::ExplicitEnumValues
+            );
+        }
+        let too_many = || format!("enum {} has too many entries for {version:?}", e.name);
+
         s.write_name(e.name.clone())?;
-        if s.header.version >= UsmapVersion::LargeEnums {
-            s.write_u16::<LE>(e.entries.len().try_into().expect("enum entries too large"))?;
+        if version >= UsmapVersion::LargeEnums {
+            s.write_u16::<LE>(e.entries.len().try_into().with_context(too_many)?)?;
         } else {
-            s.write_u8(e.entries.len().try_into().expect("enum entries too large"))?;
+            s.write_u8(e.entries.len().try_into().with_context(too_many)?)?;
         }
         for (value, name) in &e.entries {
             if s.header.version >= UsmapVersion::ExplicitEnumValues {
@@ -657,7 +745,7 @@ fn read_structs<S: Read>(s: &mut SerCtx<S>) -> Result<Vec<Struct>> {
         }
         assert_eq!(
             prop_count,
-            properties.iter().map(|p| p.array_dim as u16).sum(),
+            properties.iter().map(|p| p.array_dim as u16).sum::<u16>(),
             "prop count mismatch"
         );
         structs.push(Struct {
@@ -958,4 +1046,47 @@ mod test {
     fn test_explicit_enum_values() -> Result<()> {
         test_usmap("tests/ExplicitEnumValuesExample.usmap")
     }
+    #[test]
+    fn test_brotli() -> Result<()> {
+        let input = &mut std::io::Cursor::new(std::fs::read("tests/drg.usmap")?);
+        let res = Usmap::read(input)?;
+
+        let mut buffer = vec![];
+        res.write_compressed(&mut buffer, Some(CompressionMethod::Brotli.into()))?;
+        let res2 = Usmap::read(&mut std::io::Cursor::new(buffer))?;
+        assert_eq!(res, res2);
+        Ok(())
+    }
+    #[test]
+    fn test_downgrade_validation() -> Result<()> {
+        let res = Usmap {
+            enums: vec![Enum {
+                name: "ETest".into(),
+                entries: [(0, "A".into()), (5, "B".into())].into(),
+            }],
+            structs: vec![],
+            cext: None,
+            ppth: None,
+            eatr: None,
+            envp: None,
+        };
+        let options = WriteOptions {
+            version: UsmapVersion::LargeEnums,
+            ..Default::default()
+        };
+        assert!(res.write_with_options(&mut vec![], &options).is_err());
+        res.write_with_options(&mut vec![], &WriteOptions::default())?;
+        Ok(())
+    }
+    #[test]
+    fn test_oodle_unregistered() -> Result<()> {
+        let input = &mut std::io::Cursor::new(std::fs::read("tests/drg.usmap")?);
+        let res = Usmap::read(input)?;
+        let options = WriteOptions {
+            compression: Some(CompressionMethod::Oodle.into()),
+            ..Default::default()
+        };
+        assert!(res.write_with_options(&mut vec![], &options).is_err());
+        Ok(())
+    }
 }
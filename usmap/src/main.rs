@@ -1,11 +1,11 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::io::{BufReader, BufWriter};
 use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "usmap")]
-#[command(about = "Convert between usmap and JSON", long_about = None)]
+#[command(about = "Convert between usmap, JSON and jmap, and compare usmaps", long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -21,6 +21,51 @@ enum Commands {
         /// Output JSON file (stdout if not specified)
         output: Option<PathBuf>,
     },
+    /// Convert usmap to a skeletal .jmap (addresses, offsets and sizes are unknown)
+    ToJmap {
+        /// Input usmap file
+        input: PathBuf,
+
+        /// Output .jmap file (stdout if not specified)
+        output: Option<PathBuf>,
+    },
+    /// Re-write a usmap (or usmap JSON) targeting a specific version, compression and extensions
+    Convert {
+        /// Input usmap or .json file
+        input: PathBuf,
+
+        /// Output usmap file
+        output: PathBuf,
+
+        /// Target usmap version
+        #[arg(long, value_enum, default_value = "explicit-enum-values")]
+        target_version: UsmapVersion,
+
+        /// Compression method (uncompressed if not specified)
+        #[arg(long, value_enum)]
+        compression: Option<CompressionMethod>,
+
+        /// Compression level, codec specific
+        #[arg(long, requires = "compression")]
+        level: Option<i32>,
+
+        /// Extensions to keep (all if not specified, pass an empty value to strip all)
+        #[arg(long, value_enum, value_delimiter = ',', num_args = 0..)]
+        ext: Option<Vec<Extension>>,
+    },
+    /// Compare two usmaps by struct, enum and property
+    Diff {
+        /// Old (or reference) usmap
+        old: PathBuf,
+
+        /// New usmap
+        new: PathBuf,
+
+        /// Output a JSON Patch (RFC 6902) from the JSON of the old usmap to the new one
+        /// instead of text
+        #[arg(long)]
+        json: bool,
+    },
     /// Convert JSON to usmap
     FromJson {
         /// Input JSON file
@@ -31,6 +76,64 @@ enum Commands {
     },
 }
 
+/// CLI names of [`usmap::UsmapVersion`]
+#[derive(Clone, Copy, ValueEnum)]
+enum UsmapVersion {
+    Initial,
+    PackageVersioning,
+    LongFName,
+    LargeEnums,
+    ExplicitEnumValues,
+}
+impl From<UsmapVersion> for usmap::UsmapVersion {
+    fn from(version: UsmapVersion) -> Self {
+        match version {
+            UsmapVersion::Initial => Self::Initial,
+            UsmapVersion::PackageVersioning => Self::PackageVersioning,
+            UsmapVersion::LongFName => Self::LongFName,
+            UsmapVersion::LargeEnums => Self::LargeEnums,
+            UsmapVersion::ExplicitEnumValues => Self::ExplicitEnumValues,
+        }
+    }
+}
+
+/// CLI names of [`usmap::CompressionMethod`]
+#[derive(Clone, Copy, ValueEnum)]
+enum CompressionMethod {
+    Oodle,
+    Brotli,
+    Zstd,
+}
+impl From<CompressionMethod> for usmap::CompressionMethod {
+    fn from(method: CompressionMethod) -> Self {
+        match method {
+            CompressionMethod::Oodle => Self::Oodle,
+            CompressionMethod::Brotli => Self::Brotli,
+            CompressionMethod::Zstd => Self::Zstd,
+        }
+    }
+}
+
+/// CLI names of [`usmap::Extension`]
+#[derive(Clone, Copy, ValueEnum)]
+enum Extension {
+    /// Package paths
+    Ppth,
+    /// Enum, struct and property flags
+    Eatr,
+    /// Full enum names and values
+    Envp,
+}
+impl From<Extension> for usmap::Extension {
+    fn from(ext: Extension) -> Self {
+        match ext {
+            Extension::Ppth => Self::Ppth,
+            Extension::Eatr => Self::Eatr,
+            Extension::Envp => Self::Envp,
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -53,6 +156,86 @@ fn main() -> Result<()> {
                     .context("Failed to write JSON")?;
             }
         }
+        Commands::ToJmap { input, output } => {
+            let input_file = std::fs::File::open(&input)
+                .with_context(|| format!("Failed to open input file: {}", input.display()))?;
+            let usmap = usmap::Usmap::read(&mut BufReader::new(input_file))
+                .context("Failed to parse usmap")?;
+            let jmap = usmap.to_jmap();
+
+            if let Some(output_path) = output {
+                let output_file = std::fs::File::create(&output_path).with_context(|| {
+                    format!("Failed to create output file: {}", output_path.display())
+                })?;
+                serde_json::to_writer_pretty(BufWriter::new(output_file), &jmap)
+                    .context("Failed to write jmap")?;
+            } else {
+                serde_json::to_writer_pretty(std::io::stdout(), &jmap)
+                    .context("Failed to write jmap")?;
+            }
+        }
+        Commands::Convert {
+            input,
+            output,
+            target_version,
+            compression,
+            level,
+            ext,
+        } => {
+            let input_file = std::fs::File::open(&input)
+                .with_context(|| format!("Failed to open input file: {}", input.display()))?;
+            let usmap: usmap::Usmap = if input.extension().is_some_and(|e| e == "json") {
+                serde_json::from_reader(BufReader::new(input_file))
+                    .context("Failed to parse JSON")?
+            } else {
+                usmap::Usmap::read(&mut BufReader::new(input_file))
+                    .context("Failed to parse usmap")?
+            };
+
+            let defaults = usmap::WriteOptions::default();
+            let options = usmap::WriteOptions {
+                version: target_version.into(),
+                compression: compression.map(|method| usmap::Compression {
+                    method: method.into(),
+                    level,
+                }),
+                extensions: ext.map_or(defaults.extensions, |ext| {
+                    ext.into_iter().map(Into::into).collect()
+                }),
+            };
+
+            let output_file = std::fs::File::create(&output)
+                .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+            usmap
+                .write_with_options(&mut BufWriter::new(output_file), &options)
+                .context("Failed to write usmap")?;
+        }
+        Commands::Diff { old, new, json } => {
+            let read = |path: &PathBuf| -> Result<usmap::Usmap> {
+                let file = std::fs::File::open(path)
+                    .with_context(|| format!("Failed to open input file: {}", path.display()))?;
+                usmap::Usmap::read(&mut BufReader::new(file))
+                    .with_context(|| format!("Failed to parse usmap: {}", path.display()))
+            };
+            let (old, new) = (read(&old)?, read(&new)?);
+
+            if json {
+                let patch = jmap::diff::json_patch(
+                    &serde_json::to_value(&old)?,
+                    &serde_json::to_value(&new)?,
+                );
+                serde_json::to_writer_pretty(std::io::stdout(), &patch)
+                    .context("Failed to write JSON")?;
+                println!();
+            } else {
+                let diff = old.diff(&new);
+                if diff.is_empty() {
+                    println!("No differences");
+                } else {
+                    print!("{diff}");
+                }
+            }
+        }
         Commands::FromJson { input, output } => {
             let input_file = std::fs::File::open(&input)
                 .with_context(|| format!("Failed to open input file: {}", input.display()))?;